@@ -1,29 +1,58 @@
 mod plugin;
 
-use avian3d::PhysicsPlugins;
+use avian3d::prelude::{Collider, PhysicsPlugins, RigidBody};
 use bevy::{
     pbr::{Atmosphere, light_consts::lux},
     prelude::*,
     render::camera::Exposure,
 };
+use bevy_enhanced_input::prelude::Fired;
 use examples_common::{
     ExampleCommonPlugin,
-    camera::{FollowOffset, MainCamera, Targeting},
-    input::default_input_contexts,
+    camera::{
+        FaceCameraYaw, FollowOffset, MainCamera, TargetedBy, Targeting, fly_camera::FlyingCamera,
+        orbit_camera::HeadBob,
+    },
+    input::{Phase, default_input_contexts},
+};
+use plugin::{
+    AlignToSlope, Character, CharacterSystems, Crouch, GroundCheckOverride, KCCPlugin, Landed,
+    OneWayPlatform, input_consumed_by_fly_camera,
 };
-use plugin::{Character, KCCPlugin};
 
 const CHARACTER_RADIUS: f32 = 0.35;
 const CHARACTER_CAPSULE_LENGTH: f32 = 1.0;
+const CROUCH_CAPSULE_LENGTH: f32 = 0.3;
 const MOVEMENT_SPEED: f32 = 8.0;
 const GROUND_ACCELERATION: f32 = 100.0;
 const AIR_ACCELERATION: f32 = 40.0;
+const WALL_RUN_ACCELERATION: f32 = 20.0;
+const AIR_TURN_RATE: f32 = std::f32::consts::PI; // radians per second the horizontal velocity can turn in the air
 const FRICTION: f32 = 60.0;
+const STOP_SPEED: f32 = 0.1; // grounded characters without input stop outright below this speed
 const WALKABLE_ANGLE: f32 = std::f32::consts::PI / 4.0;
-const JUMP_IMPULSE: f32 = 6.0;
+const WALKABLE_ANGLE_HYSTERESIS: f32 = std::f32::consts::PI / 60.0; // 3°, surfaces need to be this much past the walkable angle to change classification
+const JUMP_GAP: f32 = 4.8; // the jump clears gaps this wide when moving at full speed
+const JUMP_IMPULSE: f32 = Character::jump_impulse_for_gap(JUMP_GAP, GRAVITY, MOVEMENT_SPEED);
+const DASH_SPEED: f32 = 20.0;
+const JUMP_CUT_MULTIPLIER: f32 = 0.5; // the upward velocity is scaled by this when releasing jump early
+const COYOTE_TIME: f32 = 0.1; // characters can still jump this long after walking off a ledge
+const JUMP_BUFFER: f32 = 0.1; // jumps pressed this long before landing are still performed
+const MAX_LAND_SPEED: f32 = 20.0; // landing at this speed or faster has the full impact strength
+const STEEP_LANDING_RETENTION: f32 = 0.5; // fraction of the impact speed kept when landing on a too steep slope
 const GRAVITY: f32 = 20.0; // realistic earth gravity tends to feel wrong for games
+const SLOPE_HOLD_ANGLE: f32 = std::f32::consts::PI / 6.0; // grounded characters slide down walkable slopes steeper than this
+const MAX_SLOPE_SLIDE_SPEED: f32 = 4.0;
 const STEP_HEIGHT: f32 = 0.25;
 const GROUND_CHECK_DISTANCE: f32 = 0.1;
+const MAX_GROUND_SNAP_DISTANCE: f32 = 0.25; // the ground check never pulls the character down further than this per update
+const GROUND_CHECK_SPEED_SCALE: f32 = 1.0; // extends the ground check by the horizontal motion, enough for slopes up to 45°
+const MAX_PLATFORM_CARRY_SPEED: f32 = 50.0; // faster platforms detach the character, independent of the frame rate
+const PLATFORM_INHERIT_SCALE: f32 = 1.0; // how much of the platform velocity is kept when leaving it
+const MAX_INHERITED_PLATFORM_SPEED: f32 = 20.0;
+const PHASE_DURATION: f32 = 0.5; // how long the phase input lets the character move through walls
+const PLAYER_SPAWN: Vec3 = Vec3::new(0.0, 10.5, 0.0);
+const RESPAWN_HEIGHT: f32 = -20.0; // characters falling below this height are moved back to their spawn point
 
 fn main() -> AppExit {
     App::new()
@@ -34,6 +63,12 @@ fn main() -> AppExit {
             KCCPlugin,
         ))
         .add_systems(Startup, setup)
+        .add_systems(Update, update_hud)
+        .add_systems(
+            FixedUpdate,
+            (respawn, planet_gravity).before(CharacterSystems::Accelerate),
+        )
+        .add_observer(phase_input)
         .run()
 }
 
@@ -44,21 +79,31 @@ fn setup(
 ) {
     let character = commands
         .spawn((
-            Transform::from_xyz(0.0, 10.5, 0.0),
+            Transform::from_translation(PLAYER_SPAWN),
+            SpawnPoint(PLAYER_SPAWN),
             default_input_contexts(),
             Character::default(),
-            Mesh3d(meshes.add(Capsule3d::new(CHARACTER_RADIUS, CHARACTER_CAPSULE_LENGTH))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: Color::WHITE.with_alpha(0.25),
-                alpha_mode: AlphaMode::Blend,
-                ..Default::default()
-            })),
+            Crouch::default(),
+            PlanetWalker,
+            FaceCameraYaw,
+            Visibility::default(),
+            // The mesh is a child so it can lean into slopes without rotating the collider
+            children![(
+                AlignToSlope::default(),
+                Mesh3d(meshes.add(Capsule3d::new(CHARACTER_RADIUS, CHARACTER_CAPSULE_LENGTH))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::WHITE.with_alpha(0.25),
+                    alpha_mode: AlphaMode::Blend,
+                    ..Default::default()
+                })),
+            )],
         ))
         .id();
 
     commands.spawn((
         MainCamera,
         Targeting(character),
+        HeadBob::default(),
         FollowOffset {
             absolute: Vec3::Y * CHARACTER_CAPSULE_LENGTH / 2.0,
             ..Default::default()
@@ -80,4 +125,177 @@ fn setup(
         },
         Transform::from_xyz(0.0, 0.5, 0.0),
     ));
+
+    // A wall with a character standing on it, it's gravity is perpendicular to the one of the player
+    commands.spawn((
+        RigidBody::Static,
+        Collider::cuboid(1.0, 10.0, 10.0),
+        Mesh3d(meshes.add(Cuboid::new(1.0, 10.0, 10.0))),
+        MeshMaterial3d(materials.add(StandardMaterial::default())),
+        Transform::from_xyz(10.0, 5.0, 0.0),
+    ));
+
+    let mut wall_character = Character::default();
+    wall_character.up_override = Some(Dir3::NEG_X);
+
+    commands.spawn((
+        Transform::from_xyz(8.5, 5.0, 0.0)
+            .with_rotation(Quat::from_rotation_arc(Vec3::Y, Vec3::NEG_X)),
+        wall_character,
+        Mesh3d(meshes.add(Capsule3d::new(CHARACTER_RADIUS, CHARACTER_CAPSULE_LENGTH))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::WHITE.with_alpha(0.25),
+            alpha_mode: AlphaMode::Blend,
+            ..Default::default()
+        })),
+    ));
+
+    // A platform the player can jump up through and land on
+    commands.spawn((
+        OneWayPlatform,
+        RigidBody::Static,
+        Collider::cuboid(4.0, 0.2, 4.0),
+        Mesh3d(meshes.add(Cuboid::new(4.0, 0.2, 4.0))),
+        MeshMaterial3d(materials.add(StandardMaterial::default())),
+        Transform::from_xyz(0.0, 2.5, 10.0),
+    ));
+
+    // A small planet the player can walk around
+    commands.spawn((
+        Planet { reach: 10.0 },
+        RigidBody::Static,
+        Collider::sphere(4.0),
+        Mesh3d(meshes.add(Sphere::new(4.0))),
+        MeshMaterial3d(materials.add(StandardMaterial::default())),
+        Transform::from_xyz(-15.0, 6.0, 0.0),
+    ));
+
+    commands.spawn((
+        CharacterHud,
+        Text::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            ..Default::default()
+        },
+    ));
+}
+
+/// Shows the state of the character followed by the [`MainCamera`], e.g. to drive animations from.
+#[derive(Component)]
+struct CharacterHud;
+
+fn update_hud(
+    mut hud: Single<&mut Text, With<CharacterHud>>,
+    camera: Single<&Targeting, With<MainCamera>>,
+    characters: Query<(&Character, Option<&Crouch>)>,
+    mut landings: EventReader<Landed>,
+    mut last_landing: Local<Option<Landed>>,
+) {
+    // Keep showing the last landing until the next one
+    if let Some(landed) = landings
+        .read()
+        .filter(|landed| landed.entity == camera.0)
+        .last()
+    {
+        *last_landing = Some(*landed);
+    }
+
+    let Ok((character, crouch)) = characters.get(camera.0) else {
+        return;
+    };
+
+    hud.0 = [
+        format!(
+            "Speed: {:.1} m/s ({:.1} horizontal, {:.1} vertical)",
+            character.velocity().length(),
+            character.horizontal_velocity().length(),
+            character.vertical_velocity().dot(*character.up())
+        ),
+        format!("Moving: {}", character.is_moving()),
+        format!("Stepped up: {:.2} m", character.step_height()),
+        format!("Crouched: {}", crouch.is_some_and(Crouch::is_crouched)),
+        format!("Surfaces: {}", character.contact_count()),
+        format!(
+            "Touching: {:?}",
+            character
+                .contacts()
+                .iter()
+                .map(|contact| contact.kind)
+                .collect::<Vec<_>>()
+        ),
+        match character.downhill_direction() {
+            Some(direction) => format!("Downhill: {:.2}", *direction),
+            None => "Downhill: -".to_string(),
+        },
+        match *last_landing {
+            Some(landed) => format!(
+                "Last landing: {:.1} m/s ({:.0}% impact)",
+                landed.impact_speed,
+                landed.impact_strength * 100.0
+            ),
+            None => "Last landing: -".to_string(),
+        },
+    ]
+    .join("\n");
+}
+
+/// Where a character is moved back to when falling out of the level.
+#[derive(Component)]
+struct SpawnPoint(Vec3);
+
+fn respawn(mut characters: Query<(&mut Transform, &mut Character, &SpawnPoint)>) {
+    for (mut transform, mut character, spawn_point) in &mut characters {
+        if transform.translation.y > RESPAWN_HEIGHT {
+            continue;
+        }
+
+        // Don't keep falling with the speed gained below the level
+        character.teleport(spawn_point.0, &mut transform, true);
+
+        // Snap to the ground right away if the spawn point is just above it
+        character.override_ground_check(GroundCheckOverride::Force);
+    }
+}
+
+fn phase_input(
+    trigger: Trigger<Fired<Phase>>,
+    mut characters: Query<(&mut Character, Option<&TargetedBy>)>,
+    fly_cameras: Query<(), With<FlyingCamera>>,
+) {
+    if let Ok((mut character, targeted_by)) = characters.get_mut(trigger.target())
+        && !input_consumed_by_fly_camera(targeted_by, &fly_cameras)
+    {
+        character.phase(PHASE_DURATION);
+    }
+}
+
+/// Pulls [`PlanetWalker`] characters within `reach` towards the center of the planet.
+#[derive(Component)]
+struct Planet {
+    reach: f32,
+}
+
+#[derive(Component)]
+struct PlanetWalker;
+
+fn planet_gravity(
+    planets: Query<(&Transform, &Planet)>,
+    mut walkers: Query<(&mut Transform, &mut Character), (With<PlanetWalker>, Without<Planet>)>,
+) {
+    for (mut transform, mut character) in &mut walkers {
+        character.up_override = planets.iter().find_map(|(planet_transform, planet)| {
+            let offset = transform.translation - planet_transform.translation;
+
+            match offset.length() < planet.reach {
+                true => Dir3::new(offset).ok(),
+                false => None,
+            }
+        });
+
+        // Keep the capsule upright relative to the planet surface
+        let up = character.up_override.unwrap_or(Dir3::Y);
+        transform.rotation = Quat::from_rotation_arc(*transform.up(), *up) * transform.rotation;
+    }
 }
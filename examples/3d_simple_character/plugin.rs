@@ -1,34 +1,97 @@
 use crate::{
-    AIR_ACCELERATION, CHARACTER_CAPSULE_LENGTH, CHARACTER_RADIUS, FRICTION, GRAVITY,
-    GROUND_ACCELERATION, GROUND_CHECK_DISTANCE, JUMP_IMPULSE, MOVEMENT_SPEED, STEP_HEIGHT,
-    WALKABLE_ANGLE,
+    AIR_ACCELERATION, AIR_TURN_RATE, CHARACTER_CAPSULE_LENGTH, CHARACTER_RADIUS, COYOTE_TIME,
+    CROUCH_CAPSULE_LENGTH, DASH_SPEED, FRICTION, GRAVITY, GROUND_ACCELERATION,
+    GROUND_CHECK_DISTANCE, GROUND_CHECK_SPEED_SCALE, JUMP_BUFFER, JUMP_CUT_MULTIPLIER,
+    JUMP_IMPULSE, MAX_GROUND_SNAP_DISTANCE, MAX_INHERITED_PLATFORM_SPEED, MAX_LAND_SPEED,
+    MAX_PLATFORM_CARRY_SPEED, MAX_SLOPE_SLIDE_SPEED, MOVEMENT_SPEED, PLATFORM_INHERIT_SCALE,
+    SLOPE_HOLD_ANGLE, STEEP_LANDING_RETENTION, STEP_HEIGHT, STOP_SPEED, WALKABLE_ANGLE,
+    WALKABLE_ANGLE_HYSTERESIS, WALL_RUN_ACCELERATION,
 };
-use avian3d::{prelude::*, sync::PreviousGlobalTransform};
-use bevy::prelude::*;
-use bevy_enhanced_input::prelude::{ActionState, Actions};
+use avian3d::prelude::*;
+use bevy::{
+    ecs::entity::hash_set::EntityHashSet, prelude::*, reflect::serde::TypedReflectSerializer,
+};
+use bevy_enhanced_input::prelude::{ActionState, Actions, Fired};
 use examples_common::{
-    Frozen,
-    camera::MainCamera,
-    input::{self, DefaultContext, Jump},
+    CharacterMotion, CharacterUp,
+    camera::{MainCamera, TargetedBy, fly_camera::FlyingCamera},
+    input::{self, Dash, DefaultContext, DumpState, FlyCameraContext, Jump, ReleaseJump},
 };
 use kcc_prototype::{
     character::{
-        Ground, ground_check, is_walkable, motion_on_point, project_motion_on_ground,
-        project_motion_on_wall, try_climb_step,
+        CharacterInput, CharacterShape, Ground, GroundCheckParams, SurfaceWalkability,
+        apply_slope_gravity, apply_slope_slide, downhill_direction, ground_check, is_ceiling,
+        is_ledge_edge, is_walkable, motion_on_point, project_motion_on_ceiling,
+        project_motion_on_ground, project_motion_on_wall, steep_landing_velocity, try_climb_step,
+        try_step_down, twist_angle, walkable_angle_with_hysteresis,
+    },
+    move_and_slide::{
+        MoveAndSlideConfig, MoveAndSlideContext, depenetrate, move_and_slide, sweep_check,
     },
-    move_and_slide::{MoveAndSlideConfig, move_and_slide, sweep_check},
 };
 use std::f32::consts::PI;
 
+/// The minimum distance the character has to move within an update to be considered moving.
+const MOVING_THRESHOLD: f32 = 1e-3;
+
 pub struct KCCPlugin;
 
 impl Plugin for KCCPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedPreUpdate, update_character_filter);
+        app.configure_sets(
+            FixedUpdate,
+            (
+                CharacterSystems::Accelerate,
+                CharacterSystems::PreIntegrate,
+                CharacterSystems::Integrate,
+            )
+                .chain(),
+        );
+        app.register_type::<CharacterSnapshot>();
+        app.register_type::<CharacterGravity>();
+        app.register_type::<MoveAndSlideConfig>();
+        app.init_resource::<CharacterGravity>();
+        app.init_resource::<CharacterUpdateBudget>();
+        app.add_event::<Landed>();
+        app.add_observer(dump_character_state);
+        app.add_observer(release_jump);
+        app.add_observer(dash_input);
+        app.init_resource::<ExcludedColliders>();
+        app.add_systems(
+            FixedPreUpdate,
+            (update_excluded_colliders, update_character_filter).chain(),
+        );
         app.add_systems(
             FixedUpdate,
-            (movement, platform_movement.after(PhysicsSet::Sync)),
+            (
+                blend_movement_config.before(CharacterSystems::Accelerate),
+                character_to_world_space
+                    .before(CharacterSystems::Accelerate)
+                    .before(platform_movement),
+                collect_movement_modifiers
+                    .after(character_to_world_space)
+                    .before(CharacterSystems::Accelerate),
+                character_to_local_space
+                    .after(CharacterSystems::Integrate)
+                    .after(platform_movement),
+                accelerate.in_set(CharacterSystems::Accelerate),
+                crouch
+                    .after(CharacterSystems::Accelerate)
+                    .before(CharacterSystems::PreIntegrate),
+                // Once for colliders changed outside of the movement and again after crouching swapped the collider
+                update_step_collider.before(CharacterSystems::Accelerate),
+                update_step_collider
+                    .after(crouch)
+                    .before(CharacterSystems::PreIntegrate),
+                movement.in_set(CharacterSystems::Integrate),
+                platform_movement
+                    .after(PhysicsSet::Sync)
+                    .after(CharacterSystems::Integrate),
+                sync_character_up.after(CharacterSystems::Integrate),
+                sync_character_motion.after(CharacterSystems::Integrate),
+            ),
         );
+        app.add_systems(Update, align_to_slope);
         app.add_systems(
             RunFixedMainLoop,
             jump_input.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
@@ -36,42 +99,376 @@ impl Plugin for KCCPlugin {
     }
 }
 
+/// The order in which a [`Character`] is moved within [`FixedUpdate`].
+#[derive(SystemSet, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CharacterSystems {
+    /// Friction, gravity and the input acceleration are applied to the velocity.
+    Accelerate,
+    /// Custom forces (wind, currents, etc.) can be applied here, e.g. using [`Character::launch`].
+    ///
+    /// This runs after all of the acceleration and right before the velocity is integrated.
+    PreIntegrate,
+    /// The velocity is integrated using `move_and_slide` and the ground is updated.
+    Integrate,
+}
+
 /// Cache the [`SpatialQueryFilter`] of the character to avoid re-allocating the excluded entities map every time it's used.
 ///
 /// This has to be a seperate component because otherwise the `character` cannot be mutated during a `move_and_slide` loop.
 #[derive(Component, Reflect, Default, Debug)]
 #[reflect(Component)]
-struct CharacterFilter(SpatialQueryFilter);
+struct CharacterFilter(
+    SpatialQueryFilter,
+    /// Whether the colliders of dynamic bodies are excluded, see [`DynamicBodyResponse::Ignore`].
+    bool,
+);
+
+/// The sensors and colliders of dynamic bodies in the world.
+///
+/// This is kept up to date incrementally so the [`CharacterFilter`]s don't have to search every collider each frame.
+#[derive(Resource, Default, Debug)]
+struct ExcludedColliders {
+    sensors: EntityHashSet,
+    dynamic: EntityHashSet,
+}
+
+fn update_excluded_colliders(
+    mut excluded: ResMut<ExcludedColliders>,
+    added_sensors: Query<Entity, Added<Sensor>>,
+    mut removed_sensors: RemovedComponents<Sensor>,
+    changed_bodies: Query<
+        (&RigidBody, &RigidBodyColliders),
+        Or<(Changed<RigidBody>, Changed<RigidBodyColliders>)>,
+    >,
+    body_colliders: Query<&RigidBodyColliders>,
+    mut removed_bodies: RemovedComponents<RigidBody>,
+    mut removed_colliders: RemovedComponents<ColliderOf>,
+) {
+    for entity in &added_sensors {
+        excluded.sensors.insert(entity);
+    }
+    for entity in removed_sensors.read() {
+        excluded.sensors.remove(&entity);
+    }
+
+    // Colliders that are detached from their body, or despawned
+    for entity in removed_colliders.read() {
+        excluded.dynamic.remove(&entity);
+    }
+    // Bodies that are no longer rigid bodies but still have colliders attached
+    for entity in removed_bodies.read() {
+        for collider in body_colliders
+            .get(entity)
+            .into_iter()
+            .flat_map(|colliders| colliders.iter())
+        {
+            excluded.dynamic.remove(&collider);
+        }
+    }
+    // Bodies that changed type or got new colliders attached
+    for (rigid_body, colliders) in &changed_bodies {
+        for collider in colliders.iter() {
+            match rigid_body.is_dynamic() {
+                true => excluded.dynamic.insert(collider),
+                false => excluded.dynamic.remove(&collider),
+            };
+        }
+    }
+}
 
 fn update_character_filter(
-    mut query: Query<(Entity, &mut CharacterFilter, &CollisionLayers)>,
-    sensors: Query<Entity, With<Sensor>>,
+    mut query: Query<(
+        Entity,
+        &Character,
+        &mut CharacterFilter,
+        Ref<CollisionLayers>,
+    )>,
+    excluded: Res<ExcludedColliders>,
 ) {
-    for (entity, mut filter, collidion_layers) in &mut query {
+    for (entity, character, mut filter, collision_layers) in &mut query {
+        let ignore_dynamic = character.dynamic_body_response == DynamicBodyResponse::Ignore;
+
+        // Only rebuild the filter when something changed, the excluded entities can be many
+        if !excluded.is_changed()
+            && !collision_layers.is_changed()
+            && !filter.is_added()
+            && filter.1 == ignore_dynamic
+        {
+            continue;
+        }
+
         // Filter out any entities that's not in the character's collision filter
-        filter.0.mask = collidion_layers.filters.into();
+        filter.0.mask = collision_layers.filters;
+        filter.1 = ignore_dynamic;
 
         // Filter out all sensor entities along with the character entity
         filter.0.excluded_entities.clear();
         filter
             .0
             .excluded_entities
-            .extend(sensors.iter().chain([entity]));
+            .extend(excluded.sensors.iter().copied().chain([entity]));
+
+        // Move straight through dynamic bodies
+        if ignore_dynamic {
+            filter
+                .0
+                .excluded_entities
+                .extend(excluded.dynamic.iter().copied());
+        }
+    }
+}
+
+/// The collider used to probe for steps, cached to avoid building a new collider for every step.
+///
+/// This is only set for [`Character::flat_foot_stepping`], other characters step with their own [`Collider`].
+#[derive(Component, Default)]
+struct StepCollider(Option<Collider>);
+
+fn update_step_collider(mut query: Query<(Ref<Collider>, &Character, &mut StepCollider)>) {
+    for (collider, character, mut step_collider) in &mut query {
+        if !collider.is_changed() && step_collider.0.is_some() == character.flat_foot_stepping {
+            continue;
+        }
+
+        step_collider.0 = CharacterShape::from_collider(&collider)
+            .filter(|_| character.flat_foot_stepping)
+            .map(|shape| shape.flat_bottomed().collider());
+    }
+}
+
+/// Lets a [`Character`] crouch by shrinking it's capsule collider while the crouch input is held.
+///
+/// The character only stands back up when there's enough room for the taller capsule.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Crouch {
+    pub radius: f32,
+    /// The length of the capsule while standing.
+    pub standing_length: f32,
+    /// The length of the capsule while crouching.
+    pub crouching_length: f32,
+    crouched: bool,
+}
+
+impl Crouch {
+    /// Returns `true` if the character is currently crouching.
+    pub fn is_crouched(&self) -> bool {
+        self.crouched
+    }
+}
+
+impl Default for Crouch {
+    fn default() -> Self {
+        Self {
+            radius: CHARACTER_RADIUS,
+            standing_length: CHARACTER_CAPSULE_LENGTH,
+            crouching_length: CROUCH_CAPSULE_LENGTH,
+            crouched: false,
+        }
+    }
+}
+
+fn crouch(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Transform,
+        &Collider,
+        &mut Crouch,
+        &mut Character,
+        &CharacterInput,
+        &MoveAndSlideConfig,
+        &CharacterFilter,
+    )>,
+    spatial_query: SpatialQuery,
+) {
+    for (entity, mut transform, collider, mut crouch, mut character, input, config, filter) in
+        &mut query
+    {
+        if input.crouch == crouch.crouched {
+            continue;
+        }
+
+        let height_difference = crouch.standing_length - crouch.crouching_length;
+
+        // Grounded characters keep their feet in place, airborne ones pull their legs up instead
+        let shrink_direction = match character.grounded() {
+            true => -character.up,
+            false => character.up,
+        };
+        let offset = shrink_direction * height_difference / 2.0;
+
+        if input.crouch {
+            transform.translation += offset;
+            // Resizing isn't movement, leave it out of the motion measured since the start of the update
+            character.start_translation += offset;
+            // The spatial query reads every collider, so the collider is swapped using commands
+            commands
+                .entity(entity)
+                .insert(Collider::capsule(crouch.radius, crouch.crouching_length));
+            crouch.crouched = true;
+            continue;
+        }
+
+        // Only stand up if the taller capsule fits, e.g. not below a low ceiling
+        if sweep_check(
+            collider,
+            config.epsilon,
+            config.ignore_origin_penetration,
+            transform.translation,
+            -shrink_direction,
+            height_difference,
+            transform.rotation,
+            &spatial_query,
+            &filter.0,
+        )
+        .is_some()
+        {
+            continue;
+        }
+
+        transform.translation -= offset;
+        character.start_translation -= offset;
+        commands
+            .entity(entity)
+            .insert(Collider::capsule(crouch.radius, crouch.standing_length));
+        crouch.crouched = false;
     }
 }
 
+/// Ignores the input of a [`Character`] while gravity, friction and collisions keep working, e.g. for cutscenes or stuns.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct InputFrozen;
+
+/// A platform [`Character`]s only collide with from above, they pass through it from below and the sides.
+///
+/// The top of the platform is the `up` direction of it's [`GlobalTransform`].
+#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct OneWayPlatform;
+
+/// A surface a [`Character`] touched.
+#[derive(Reflect, Debug, PartialEq, Clone, Copy)]
+pub struct ContactInfo {
+    pub entity: Entity,
+    pub normal: Dir3,
+    /// The world-space point where the character touched the surface.
+    pub point: Vec3,
+    pub kind: SurfaceKind,
+}
+
+/// The kind of surface a [`Character`] touched, relative to it's `up` direction.
+#[derive(Reflect, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SurfaceKind {
+    /// A walkable surface.
+    Ground,
+    /// A surface too steep to walk on.
+    Wall,
+    /// A surface facing down.
+    Ceiling,
+}
+
+/// Overrides the automatic ground check of the next movement update, see [`Character::override_ground_check`].
+#[derive(Reflect, Debug, PartialEq, Clone, Copy)]
+pub enum GroundCheckOverride {
+    /// Check for ground even when airborne, snapping the character to the ground below.
+    Force,
+    /// Don't check for ground, the character only becomes grounded if it hits walkable ground while moving.
+    Skip,
+}
+
+/// How a [`Character`] responds to hitting a dynamic rigid body.
+#[derive(Reflect, Default, Debug, PartialEq, Clone, Copy)]
+pub enum DynamicBodyResponse {
+    /// Slide along the body like a wall, without standing or stepping on it.
+    Block,
+    /// Slide along the body like a wall and push it along with the character.
+    Push,
+    /// Move straight through the body.
+    Ignore,
+    /// Treat the body like static geometry, allowing the character to stand and step on it.
+    #[default]
+    StepOn,
+}
+
 #[derive(Component)]
 #[require(
     RigidBody = RigidBody::Kinematic,
     Collider = Capsule3d::new(CHARACTER_RADIUS, CHARACTER_CAPSULE_LENGTH),
     CharacterFilter,
+    StepCollider,
+    CharacterUp,
+    CharacterMotion,
+    MovementConfig,
+    CombinedMovementModifier,
+    CharacterInput,
+    MoveAndSlideConfig,
 )]
 pub struct Character {
     velocity: Vec3,
     ground: Option<Ground>,
     previous_ground: Option<Ground>,
+    /// The platform the character is standing on and it's transform when the platform movement was last applied.
+    platform_transform: Option<(Entity, GlobalTransform)>,
+    /// The normal of the wall the character is touching while airborne.
+    wall: Option<Dir3>,
+    /// The translation applied by the last movement update, excluding platform movement.
+    motion: Vec3,
+    /// The translation at the start of the movement update.
+    start_translation: Vec3,
+    /// The velocity at the start of the movement update, before any acceleration.
+    start_velocity: Vec3,
+    /// The ground and wall found while accelerating, these are carried over to the integration.
+    new_ground: Option<Ground>,
+    new_wall: Option<Dir3>,
+    /// Whether the character stepped up during the current update.
+    stepped_up: bool,
+    /// The total height climbed by stepping up during the last update.
+    step_height: f32,
+    /// Whether the character stepped up during the last update, keeping it grounded for one more update.
+    step_grace: bool,
+    /// The number of distinct surfaces the character collided with during the last update.
+    contact_count: usize,
+    /// Everything the character touched during the last update, reused to avoid allocating every update.
+    contacts: Vec<ContactInfo>,
+    /// The remaining time in seconds the character ignores collisions for.
+    phase_time: f32,
+    /// The remaining time in seconds the character can still jump after leaving the ground.
+    coyote_time: f32,
+    /// Whether the character jumped since it last landed.
+    jumped: bool,
+    /// The time in seconds since a jump was requested while airborne, see [`Character::request_jump`].
+    jump_request_age: Option<f32>,
+    ground_check_override: Option<GroundCheckOverride>,
     up: Dir3,
-    config: MoveAndSlideConfig,
+    /// Probe steps with a flat-bottom cylinder instead of the capsule for more precise step heights.
+    pub flat_foot_stepping: bool,
+    /// Disable to never step up, sliding into every obstacle instead.
+    pub enable_step_up: bool,
+    /// Where to sample the motion of the platform the character is standing on.
+    pub platform_sample_origin: PlatformSampleOrigin,
+    /// Turn along with rotating platforms, only about `up`.
+    ///
+    /// Disable to keep the facing fixed on turntables, the character is still carried along.
+    pub inherit_platform_rotation: bool,
+    /// The maximum speed in radians per second the character turns along with rotating platforms.
+    pub max_platform_angular_speed: f32,
+    /// How much of the platform velocity is kept when leaving the platform, `0.0` doesn't inherit any of it.
+    pub platform_inherit_scale: f32,
+    /// The maximum speed inherited from the platform when leaving it, avoids launching the character off fast platforms.
+    pub max_inherited_platform_speed: f32,
+    /// Only inherit the horizontal platform velocity when leaving a platform without jumping, e.g. walking off
+    /// a platform moving up doesn't launch the character.
+    pub inherit_horizontal_platform_velocity_only: bool,
+    /// How the character moves through the world.
+    pub movement_mode: MovementMode,
+    /// Use this `up` direction instead of the one opposing the [`CharacterGravity`].
+    pub up_override: Option<Dir3>,
+    /// How the velocity is integrated into the translation.
+    pub integrator: Integrator,
+    /// How the character responds to hitting dynamic rigid bodies.
+    pub dynamic_body_response: DynamicBodyResponse,
 }
 
 impl Character {
@@ -89,15 +486,207 @@ impl Character {
 
     /// Launch the character on the `up` axis, overriding the downward velocity.
     pub fn jump(&mut self, impulse: f32) {
+        // Can't jump again before landing
+        self.coyote_time = 0.0;
+        self.jumped = true;
+        self.jump_request_age = None;
+
         // Override downward velocity
         let down = self.velocity.dot(*self.up).min(0.0);
         self.launch(self.up * impulse + self.up * -down);
     }
 
+    /// Scales the upward velocity by `multiplier` while rising, e.g. when the jump input is released early for a short hop.
+    ///
+    /// Does nothing while grounded or falling.
+    pub fn cut_jump(&mut self, multiplier: f32) {
+        let upward_speed = self.velocity.dot(*self.up);
+
+        if self.grounded() || upward_speed <= 0.0 {
+            return;
+        }
+
+        self.velocity -= self.up * upward_speed * (1.0 - multiplier);
+    }
+
+    /// Jump as soon as the character lands, if it lands within [`MovementConfig::jump_buffer`] seconds.
+    pub fn request_jump(&mut self) {
+        self.jump_request_age = Some(0.0);
+    }
+
+    /// Ignore collisions for `duration` seconds, e.g. to dash through enemies.
+    ///
+    /// The character is pushed out of any geometry it ends up inside of when the phase ends.
+    pub fn phase(&mut self, duration: f32) {
+        self.phase_time = self.phase_time.max(duration);
+    }
+
+    /// Returns `true` if the character is currently ignoring collisions.
+    pub fn phasing(&self) -> bool {
+        self.phase_time > 0.0
+    }
+
+    /// Estimates the jump impulse needed to clear a gap of `horizontal_distance` while moving at `move_speed`.
+    ///
+    /// This assumes the launch and landing are at the same height and that there's no air control
+    /// or drag, so the horizontal speed stays constant. Returns [`f32::INFINITY`] when not moving.
+    pub const fn jump_impulse_for_gap(
+        horizontal_distance: f32,
+        gravity: f32,
+        move_speed: f32,
+    ) -> f32 {
+        if move_speed <= 0.0 {
+            return f32::INFINITY;
+        }
+
+        // The time spent in the air, the jump has to go up and come back down within this time
+        let air_time = horizontal_distance / move_speed;
+
+        gravity * air_time / 2.0
+    }
+
+    /// Changes the `up` direction, rotating the velocity along with it.
+    ///
+    /// This avoids a velocity discontinuity when `up` changes every update, e.g. when walking around a planet.
+    pub fn recompute_up(&mut self, new_up: Dir3) {
+        let rotation = Quat::from_rotation_arc(*self.up, *new_up);
+
+        self.velocity = rotation * self.velocity;
+        self.up = new_up;
+    }
+
+    /// Sets the horizontal velocity to `speed` along `direction` without clearing the grounded state.
+    ///
+    /// The dash follows the slope when grounded, otherwise the vertical velocity is left untouched.
+    pub fn dash(&mut self, direction: Dir3, speed: f32) {
+        let dash = direction
+            .reject_from_normalized(*self.up)
+            .normalize_or_zero()
+            * speed;
+
+        self.velocity = match self.ground {
+            Some(ground) => project_motion_on_ground(dash, ground.normal, self.up),
+            None => dash + self.vertical_velocity(),
+        };
+    }
+
+    /// Returns the velocity of the character.
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    /// Overrides the velocity of the character, e.g. for knockback or scripted movement.
+    ///
+    /// Unlike [`Character::launch`] this doesn't clear the grounded state.
+    pub fn set_velocity(&mut self, velocity: Vec3) {
+        self.velocity = velocity;
+    }
+
+    /// Returns the part of the velocity perpendicular to `up`.
+    pub fn horizontal_velocity(&self) -> Vec3 {
+        self.velocity.reject_from_normalized(*self.up)
+    }
+
+    /// Returns the part of the velocity along `up`.
+    pub fn vertical_velocity(&self) -> Vec3 {
+        self.velocity.project_onto_normalized(*self.up)
+    }
+
+    /// Returns the `up` direction of the character.
+    pub fn up(&self) -> Dir3 {
+        self.up
+    }
+
     /// Returns `true` if the character is standing on the ground.
     pub fn grounded(&self) -> bool {
         self.ground.is_some()
     }
+
+    /// Moves the character to `translation`, e.g. to respawn it or for portals.
+    ///
+    /// This is the correct way to relocate a character, setting the [`Transform`] directly keeps the old ground,
+    /// which makes the platform movement carry the character or inherit a bogus velocity on the next update.
+    /// The character is airborne until it lands again. It keeps its velocity unless `zero_velocity` is set,
+    /// e.g. portals keep it while respawning stops the character.
+    ///
+    /// Set [`FollowSmoothing::snap`](examples_common::camera::FollowSmoothing::snap) on the camera following
+    /// the character to avoid smoothing across the teleport.
+    pub fn teleport(&mut self, translation: Vec3, transform: &mut Transform, zero_velocity: bool) {
+        transform.translation = translation;
+
+        if zero_velocity {
+            self.velocity = Vec3::ZERO;
+        }
+
+        self.set_grounded(None);
+        self.previous_ground = None;
+        self.platform_transform = None;
+        self.new_ground = None;
+        self.wall = None;
+        self.new_wall = None;
+        self.step_grace = false;
+        self.contacts.clear();
+        self.contact_count = 0;
+        self.start_translation = translation;
+        self.motion = Vec3::ZERO;
+    }
+
+    /// Sets the ground the character is standing on, e.g. to force it airborne for a launch or grounded for a cutscene.
+    ///
+    /// The automatic ground check of the next movement update still runs when grounded, which replaces the ground
+    /// if there's nothing below the character. Use [`Character::override_ground_check`] to keep the ground as is.
+    pub fn set_grounded(&mut self, ground: Option<Ground>) {
+        if ground.is_none() {
+            self.coyote_time = 0.0;
+        }
+
+        self.ground = ground;
+    }
+
+    /// Forces the ground check of the next movement update to run or be skipped, regardless of the grounded state.
+    pub fn override_ground_check(&mut self, ground_check: GroundCheckOverride) {
+        self.ground_check_override = Some(ground_check);
+    }
+
+    /// Returns `true` if the character is standing on the ground or left it less than [`MovementConfig::coyote_time`] ago.
+    pub fn grounded_or_coyote(&self) -> bool {
+        self.grounded() || self.coyote_time > 0.0
+    }
+
+    /// Returns the direction of steepest descent on the current ground, `None` when airborne or on flat ground.
+    pub fn downhill_direction(&self) -> Option<Dir3> {
+        self.ground
+            .and_then(|ground| downhill_direction(*ground.normal, self.up))
+    }
+
+    /// Returns `true` if the character actually moved during the last update.
+    ///
+    /// Unlike checking the velocity, this is `false` when pushing into a wall without moving.
+    pub fn is_moving(&self) -> bool {
+        self.motion.length_squared() > MOVING_THRESHOLD * MOVING_THRESHOLD
+    }
+
+    /// Returns the height the character climbed by stepping up during the last update, e.g. to play step sounds.
+    pub fn step_height(&self) -> f32 {
+        self.step_height
+    }
+
+    /// Returns the number of distinct surfaces the character collided with during the last update.
+    ///
+    /// `0` is open space, `1` a single wall or floor and `2` or more means the character is cornered.
+    pub fn contact_count(&self) -> usize {
+        self.contact_count
+    }
+
+    /// Returns everything the character touched during the last update, including the ground it's standing on.
+    pub fn contacts(&self) -> &[ContactInfo] {
+        &self.contacts
+    }
+
+    /// Returns `true` if the character is airborne and touching a wall.
+    pub fn wall_running(&self) -> bool {
+        self.ground.is_none() && self.wall.is_some()
+    }
 }
 
 impl Default for Character {
@@ -106,434 +695,3373 @@ impl Default for Character {
             velocity: Vec3::ZERO,
             ground: None,
             previous_ground: None,
+            platform_transform: None,
+            wall: None,
+            motion: Vec3::ZERO,
+            start_translation: Vec3::ZERO,
+            start_velocity: Vec3::ZERO,
+            new_ground: None,
+            new_wall: None,
+            stepped_up: false,
+            step_height: 0.0,
+            step_grace: false,
+            contact_count: 0,
+            contacts: Vec::new(),
+            phase_time: 0.0,
+            coyote_time: 0.0,
+            jumped: false,
+            jump_request_age: None,
+            ground_check_override: None,
             up: Dir3::Y,
-            config: MoveAndSlideConfig::default(),
+            flat_foot_stepping: false,
+            enable_step_up: true,
+            platform_sample_origin: PlatformSampleOrigin::default(),
+            inherit_platform_rotation: false,
+            max_platform_angular_speed: f32::INFINITY,
+            platform_inherit_scale: PLATFORM_INHERIT_SCALE,
+            max_inherited_platform_speed: MAX_INHERITED_PLATFORM_SPEED,
+            inherit_horizontal_platform_velocity_only: false,
+            movement_mode: MovementMode::default(),
+            up_override: None,
+            integrator: Integrator::default(),
+            dynamic_body_response: DynamicBodyResponse::default(),
         }
     }
 }
 
-fn jump_input(mut query: Query<(&mut Character, &Actions<DefaultContext>)>) {
-    for (mut character, actions) in &mut query {
-        if character.grounded() && actions.state::<Jump>().unwrap_or_default() == ActionState::Fired
-        {
-            character.jump(JUMP_IMPULSE);
+/// The movement tuning of a [`Character`].
+#[derive(Component, Reflect, Debug, PartialEq, Clone, Copy)]
+#[reflect(Component)]
+pub struct MovementConfig {
+    pub movement_speed: f32,
+    pub ground_acceleration: f32,
+    pub air_acceleration: f32,
+    pub wall_run_acceleration: f32,
+    /// How fast the horizontal velocity can change direction in the air, in radians per second.
+    pub air_turn_rate: f32,
+    pub friction: f32,
+    /// Grounded characters without input stop completely below this speed instead of slowly crawling to a halt.
+    pub stop_speed: f32,
+    /// Scales the [`CharacterGravity`] applied to this character.
+    pub gravity_scale: f32,
+    /// How far (in radians) a surface has to be past the walkable angle to change between ground and wall,
+    /// see [`walkable_angle_with_hysteresis`].
+    pub walkable_angle_hysteresis: f32,
+    /// Grounded characters hold their position on slopes up to this angle (in radians) and slide down steeper ones.
+    pub slope_hold_angle: f32,
+    /// The tallest obstacle the character steps up onto, and the furthest it steps down when walking off a step.
+    pub max_step_height: f32,
+    /// Gravity doesn't accelerate characters sliding down a walkable slope past this speed.
+    pub max_slope_slide_speed: f32,
+    pub jump_impulse: f32,
+    /// Scales the upward velocity when the jump input is released while rising, see [`Character::cut_jump`].
+    pub jump_cut_multiplier: f32,
+    /// The speed of a [`Character::dash`] from the dash input.
+    pub dash_speed: f32,
+    /// The impact speed at which the [`Landed::impact_strength`] reaches `1.0`.
+    pub max_land_speed: f32,
+    /// The fraction of the impact speed redirected down the slope when landing on a slope too steep to stand on.
+    pub steep_landing_retention: f32,
+    /// The time in seconds the character can still jump after walking off a ledge.
+    pub coyote_time: f32,
+    /// The time in seconds a jump requested while airborne is kept around to be performed on landing.
+    pub jump_buffer: f32,
+}
+
+impl MovementConfig {
+    /// Linearly interpolates every field between `a` and `b`.
+    pub fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        Self {
+            movement_speed: a.movement_speed.lerp(b.movement_speed, t),
+            ground_acceleration: a.ground_acceleration.lerp(b.ground_acceleration, t),
+            air_acceleration: a.air_acceleration.lerp(b.air_acceleration, t),
+            wall_run_acceleration: a.wall_run_acceleration.lerp(b.wall_run_acceleration, t),
+            air_turn_rate: a.air_turn_rate.lerp(b.air_turn_rate, t),
+            friction: a.friction.lerp(b.friction, t),
+            stop_speed: a.stop_speed.lerp(b.stop_speed, t),
+            gravity_scale: a.gravity_scale.lerp(b.gravity_scale, t),
+            walkable_angle_hysteresis: a
+                .walkable_angle_hysteresis
+                .lerp(b.walkable_angle_hysteresis, t),
+            slope_hold_angle: a.slope_hold_angle.lerp(b.slope_hold_angle, t),
+            max_step_height: a.max_step_height.lerp(b.max_step_height, t),
+            max_slope_slide_speed: a.max_slope_slide_speed.lerp(b.max_slope_slide_speed, t),
+            jump_impulse: a.jump_impulse.lerp(b.jump_impulse, t),
+            jump_cut_multiplier: a.jump_cut_multiplier.lerp(b.jump_cut_multiplier, t),
+            dash_speed: a.dash_speed.lerp(b.dash_speed, t),
+            max_land_speed: a.max_land_speed.lerp(b.max_land_speed, t),
+            steep_landing_retention: a.steep_landing_retention.lerp(b.steep_landing_retention, t),
+            coyote_time: a.coyote_time.lerp(b.coyote_time, t),
+            jump_buffer: a.jump_buffer.lerp(b.jump_buffer, t),
         }
     }
 }
 
-fn platform_movement(
-    spatial_query: SpatialQuery,
-    mut query: Query<(&mut Transform, &mut Character, &Collider, &CharacterFilter)>,
-    platforms: Query<(&GlobalTransform, &PreviousGlobalTransform)>,
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            movement_speed: MOVEMENT_SPEED,
+            ground_acceleration: GROUND_ACCELERATION,
+            air_acceleration: AIR_ACCELERATION,
+            wall_run_acceleration: WALL_RUN_ACCELERATION,
+            air_turn_rate: AIR_TURN_RATE,
+            friction: FRICTION,
+            stop_speed: STOP_SPEED,
+            gravity_scale: 1.0,
+            walkable_angle_hysteresis: WALKABLE_ANGLE_HYSTERESIS,
+            slope_hold_angle: SLOPE_HOLD_ANGLE,
+            max_step_height: STEP_HEIGHT,
+            max_slope_slide_speed: MAX_SLOPE_SLIDE_SPEED,
+            jump_impulse: JUMP_IMPULSE,
+            jump_cut_multiplier: JUMP_CUT_MULTIPLIER,
+            dash_speed: DASH_SPEED,
+            max_land_speed: MAX_LAND_SPEED,
+            steep_landing_retention: STEEP_LANDING_RETENTION,
+            coyote_time: COYOTE_TIME,
+            jump_buffer: JUMP_BUFFER,
+        }
+    }
+}
+
+/// Smoothly transitions the [`MovementConfig`] of a character from `from` to `to` over `duration` seconds.
+///
+/// The component removes itself once the transition is complete, e.g. for buffs and debuffs.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct MovementConfigBlend {
+    pub from: MovementConfig,
+    pub to: MovementConfig,
+    pub duration: f32,
+    /// The time in seconds since the transition started, usually `0.0` when inserting the blend.
+    pub elapsed: f32,
+}
+
+fn blend_movement_config(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut MovementConfig, &mut MovementConfigBlend)>,
     time: Res<Time>,
 ) {
-    for (mut transform, mut character, collider, filter) in &mut query {
-        let platform_motion = |entity| {
-            platforms.get(entity).map_or(
-                Vec3::ZERO,
-                |(platform_transform, prev_platform_transform)| {
-                    motion_on_point(
-                        transform.translation,
-                        platform_transform,
-                        prev_platform_transform,
-                    )
-                },
-            )
+    for (entity, mut config, mut blend) in &mut query {
+        blend.elapsed += time.delta_secs();
+
+        let t = match blend.duration > 0.0 {
+            true => (blend.elapsed / blend.duration).min(1.0),
+            false => 1.0,
         };
 
-        match (character.ground, character.previous_ground) {
-            // Currently on the platform, follow it's movement
-            (Some(ground), ..) => {
-                let platform_motion = platform_motion(ground.entity);
+        *config = MovementConfig::lerp(&blend.from, &blend.to, t);
 
-                // Sweep in the platform movement direction to avoid passing through walls
-                if let Ok((direction, max_distance)) = Dir3::new_and_length(platform_motion) {
-                    let safe_distance = sweep_check(
-                        collider,
-                        character.config.epsilon,
-                        transform.translation,
-                        direction,
-                        max_distance,
-                        transform.rotation,
-                        &spatial_query,
-                        &filter.0,
-                    )
-                    .map(|(d, _)| d)
-                    .unwrap_or(max_distance);
+        if t >= 1.0 {
+            commands.entity(entity).remove::<MovementConfigBlend>();
+        }
+    }
+}
 
-                    transform.translation += direction * safe_distance;
-                };
-            }
-            // Left the platform, inherit the platform velocity
-            (None, Some(previous_ground)) => {
-                let platform_velocity = platform_motion(previous_ground.entity) / time.delta_secs();
-                character.velocity += platform_velocity;
-            }
-            _ => {}
+/// Scales the movement of characters inside of this sensor volume, e.g. for water or mud.
+///
+/// The scales of overlapping volumes are multiplied.
+#[derive(Component, Reflect, Debug, PartialEq, Clone, Copy)]
+#[reflect(Component)]
+#[require(Sensor)]
+pub struct MovementModifier {
+    pub gravity_scale: f32,
+    pub friction_scale: f32,
+    pub accel_scale: f32,
+    pub max_speed_scale: f32,
+}
+
+impl MovementModifier {
+    /// Returns the product of both modifiers.
+    pub fn combine(self, other: Self) -> Self {
+        Self {
+            gravity_scale: self.gravity_scale * other.gravity_scale,
+            friction_scale: self.friction_scale * other.friction_scale,
+            accel_scale: self.accel_scale * other.accel_scale,
+            max_speed_scale: self.max_speed_scale * other.max_speed_scale,
         }
+    }
 
-        character.previous_ground = character.ground;
+    /// Returns the `config` scaled by this modifier.
+    pub fn apply(&self, config: &MovementConfig) -> MovementConfig {
+        MovementConfig {
+            movement_speed: config.movement_speed * self.max_speed_scale,
+            ground_acceleration: config.ground_acceleration * self.accel_scale,
+            air_acceleration: config.air_acceleration * self.accel_scale,
+            wall_run_acceleration: config.wall_run_acceleration * self.accel_scale,
+            friction: config.friction * self.friction_scale,
+            gravity_scale: config.gravity_scale * self.gravity_scale,
+            ..*config
+        }
     }
 }
 
-fn movement(
-    mut q_kcc: Query<
-        (
-            &Actions<DefaultContext>,
-            &mut Transform,
-            &mut Character,
-            &Collider,
-            &CharacterFilter,
-            Has<Sensor>,
-        ),
-        Without<Frozen>,
-    >,
-    main_camera: Single<&Transform, (With<MainCamera>, Without<Character>)>,
-    time: Res<Time>,
-    spatial_query: SpatialQuery,
-) {
-    let main_camera_transform = main_camera.into_inner();
-    for (actions, mut transform, mut character, collider, filter, has_sensor) in &mut q_kcc {
-        // Get the raw 2D input vector
-        let input_vec = actions.value::<input::Move>().unwrap_or_default();
+impl Default for MovementModifier {
+    fn default() -> Self {
+        Self {
+            gravity_scale: 1.0,
+            friction_scale: 1.0,
+            accel_scale: 1.0,
+            max_speed_scale: 1.0,
+        }
+    }
+}
 
-        // Extract just the yaw from the camera rotation
-        let camera_yaw = main_camera_transform.rotation.to_euler(EulerRot::YXZ).0;
-        let yaw_rotation = Quat::from_rotation_y(camera_yaw);
+/// The product of every [`MovementModifier`] the character is currently inside of.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component)]
+struct CombinedMovementModifier(MovementModifier);
 
-        // Rotate the movement direction vector by only the camera's yaw
-        let direction = yaw_rotation * Vec3::new(input_vec.x, 0.0, -input_vec.y);
+fn collect_movement_modifiers(
+    spatial_query: SpatialQuery,
+    mut query: Query<
+        (Entity, &Transform, &Collider, &mut CombinedMovementModifier),
+        With<Character>,
+    >,
+    modifiers: Query<&MovementModifier>,
+) {
+    for (entity, transform, collider, mut combined) in &mut query {
+        // The character filter excludes sensors, so the volumes need their own filter
+        let filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+
+        combined.0 = spatial_query
+            .shape_intersections(collider, transform.translation, transform.rotation, &filter)
+            .into_iter()
+            .filter_map(|entity| modifiers.get(entity).ok())
+            .fold(MovementModifier::default(), |combined, modifier| {
+                combined.combine(*modifier)
+            });
+    }
+}
 
-        let max_acceleration = match character.ground {
-            Some(_) => {
-                let friction = friction(character.velocity, FRICTION, time.delta_secs());
-                character.velocity += friction;
+/// Gently pulls a descending [`Character`] onto walkable ground within reach, making landings more forgiving.
+///
+/// Unlike the ground snap this applies while still airborne, but never while rising from a jump.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct LandingMagnetism {
+    /// How far below the character walkable ground is searched for.
+    pub range: f32,
+    /// The downward acceleration towards the ground.
+    pub strength: f32,
+    /// The upward speed the character has to be below, `0.0` only pulls while falling.
+    pub activation_speed: f32,
+}
 
-                GROUND_ACCELERATION
-            }
-            None => {
-                // Apply gravity when not grounded
-                let gravity = character.up * -GRAVITY * time.delta_secs();
-                character.velocity += gravity;
+impl Default for LandingMagnetism {
+    fn default() -> Self {
+        Self {
+            range: 1.0,
+            strength: 30.0,
+            activation_speed: 0.0,
+        }
+    }
+}
 
-                AIR_ACCELERATION
-            }
-        };
+/// The point at which the motion of a platform is sampled.
+///
+/// Rotating platforms move points differently depending on their distance from the pivot,
+/// so this should be as close to where the character touches the platform as possible.
+#[derive(Reflect, Default, Debug, PartialEq, Clone, Copy)]
+pub enum PlatformSampleOrigin {
+    /// The center of the character.
+    Center,
+    /// The bottom of the character's collider.
+    Feet,
+    /// The point where the character touches the ground.
+    #[default]
+    Contact,
+}
 
-        // accelerate in the movement direction
-        let mut move_accel = acceleration(
-            character.velocity,
-            direction,
-            max_acceleration,
-            MOVEMENT_SPEED,
-            time.delta_secs(),
-        );
+/// The way a [`Character`] moves through the world.
+#[derive(Reflect, Default, Debug, PartialEq, Clone, Copy)]
+pub enum MovementMode {
+    /// Affected by gravity and only moves along the ground.
+    #[default]
+    Walking,
+    /// Not affected by gravity, the vertical input is used to move up and down.
+    Swimming,
+}
 
-        // We can skip everything if the character has a sensor component
-        if has_sensor {
-            character.velocity += move_accel;
-            transform.translation += character.velocity * time.delta_secs();
+/// The method used to integrate the velocity of a [`Character`].
+#[derive(Reflect, Default, Debug, PartialEq, Clone, Copy)]
+pub enum Integrator {
+    /// Move using the velocity from before it was accelerated.
+    Explicit,
+    /// Move using the accelerated velocity.
+    #[default]
+    SemiImplicit,
+    /// Move using the average of the velocity before and after accelerating.
+    ///
+    /// This is exact for constant accelerations like gravity, so jump heights don't depend on the timestep.
+    Midpoint,
+}
 
-            continue;
+impl Integrator {
+    /// Returns the velocity to move with given the velocity `before` and `after` accelerating.
+    pub fn velocity(self, before: Vec3, after: Vec3) -> Vec3 {
+        match self {
+            Self::Explicit => before,
+            Self::SemiImplicit => after,
+            Self::Midpoint => (before + after) * 0.5,
         }
+    }
+}
 
-        // We need to store the new ground for the ground check to work properly
-        let mut new_ground = None;
+/// Sent when a [`Character`] lands on the ground, e.g. to play a landing animation.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Landed {
+    pub entity: Entity,
+    /// The speed the character hit the ground with.
+    pub impact_speed: f32,
+    /// The `impact_speed` divided by the [`MovementConfig::max_land_speed`] and clamped to `[0, 1]`.
+    pub impact_strength: f32,
+}
 
-        if let Some(ground) = character.ground {
-            // Project acceleration on the ground plane
-            move_accel = project_motion_on_ground(move_accel, *ground.normal, character.up);
+impl Landed {
+    /// Creates the event for landing with `impact_speed`, reaching the full strength at `max_land_speed`.
+    pub fn new(entity: Entity, impact_speed: f32, max_land_speed: f32) -> Self {
+        Self {
+            entity,
+            impact_speed,
+            impact_strength: (impact_speed / max_land_speed).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Tilts a visual child of a [`Character`] towards the normal of the ground it's standing on.
+///
+/// This is purely cosmetic, the character itself keeps moving and colliding along it's `up` direction.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct AlignToSlope {
+    /// The maximum angle in radians to tilt away from the `up` direction of the character.
+    pub max_angle: f32,
+    /// How quickly the tilt follows the ground, higher is stiffer.
+    pub speed: f32,
+}
+
+impl Default for AlignToSlope {
+    fn default() -> Self {
+        Self {
+            max_angle: PI / 8.0,
+            speed: 10.0,
+        }
+    }
+}
+
+fn align_to_slope(
+    mut visuals: Query<(&mut Transform, &AlignToSlope, &ChildOf)>,
+    characters: Query<(&Character, &GlobalTransform)>,
+    time: Res<Time>,
+) {
+    for (mut transform, align, child_of) in &mut visuals {
+        let Ok((character, character_transform)) = characters.get(child_of.parent()) else {
+            continue;
+        };
+
+        // Stand upright when airborne
+        let ground_normal = character
+            .ground
+            .map_or(*character.up, |ground| *ground.normal);
+
+        let angle = character.up.angle_between(ground_normal);
+        let tilt = match Dir3::new(character.up.cross(ground_normal)) {
+            Ok(axis) => Quat::from_axis_angle(*axis, angle.min(align.max_angle)),
+            Err(_) => Quat::IDENTITY,
+        };
+
+        // The tilt is in world space, bring it into the space of the character
+        let parent_rotation = character_transform.rotation();
+        let target = parent_rotation.inverse() * tilt * parent_rotation;
+
+        let t = 1.0 - f32::exp(-align.speed * time.delta_secs());
+        transform.rotation = transform.rotation.slerp(target, t);
+    }
+}
+
+/// Limits how many characters get a full (colliding) movement update per frame, for large crowds of background characters.
+///
+/// Characters are selected round-robin so none of them are starved, the others only move as far as a single sweep
+/// allows without sliding, stepping or checking for ground.
+#[derive(Resource, Reflect, Default, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct CharacterUpdateBudget {
+    /// Update every character fully when `None`.
+    pub max_full_updates_per_frame: Option<usize>,
+    /// The index of the first character to fully update next frame.
+    cursor: usize,
+}
+
+impl CharacterUpdateBudget {
+    /// Returns `true` if the character at `index` out of `count` characters gets a full update this frame.
+    fn is_full_update(&self, index: usize, count: usize) -> bool {
+        match self.max_full_updates_per_frame {
+            Some(max) => (index + count - self.cursor % count) % count < max,
+            None => true,
+        }
+    }
+
+    /// Moves on to the next characters after `count` characters were updated.
+    fn advance(&mut self, count: usize) {
+        if let (Some(max), true) = (self.max_full_updates_per_frame, count > 0) {
+            self.cursor = (self.cursor + max) % count;
+        }
+    }
+}
+
+/// The gravity shared by all characters, the `up` direction of a character opposes it by default.
+#[derive(Resource, Reflect, Debug, PartialEq, Clone, Copy)]
+#[reflect(Resource)]
+pub struct CharacterGravity(pub Vec3);
+
+impl Default for CharacterGravity {
+    fn default() -> Self {
+        Self(Vec3::NEG_Y * GRAVITY)
+    }
+}
+
+fn sync_character_up(mut query: Query<(&Character, &mut CharacterUp)>) {
+    for (character, mut up) in &mut query {
+        up.set_if_neq(CharacterUp(character.up()));
+    }
+}
+
+fn sync_character_motion(mut query: Query<(&Character, &mut CharacterMotion)>) {
+    for (character, mut motion) in &mut query {
+        motion.set_if_neq(CharacterMotion {
+            velocity: character.velocity,
+            grounded: character.grounded(),
+        });
+    }
+}
+
+/// Moves the [`Transform`] of parented characters (e.g. mounted on a vehicle) into world space, the movement works in world space.
+///
+/// [`character_to_local_space`] moves it back once the movement is done, so the character still follows it's parent.
+fn character_to_world_space(
+    mut query: Query<(&mut Transform, &ChildOf), With<Character>>,
+    parents: Query<&GlobalTransform>,
+) {
+    for (mut transform, child_of) in &mut query {
+        if let Ok(parent) = parents.get(child_of.parent()) {
+            *transform = parent.mul_transform(*transform).compute_transform();
+        }
+    }
+}
+
+fn character_to_local_space(
+    mut query: Query<(&mut Transform, &ChildOf), With<Character>>,
+    parents: Query<&GlobalTransform>,
+) {
+    for (mut transform, child_of) in &mut query {
+        if let Ok(parent) = parents.get(child_of.parent()) {
+            *transform = GlobalTransform::from(*transform).reparented_to(parent);
+        }
+    }
+}
+
+/// Returns the delta time of a character, scaled by it's [`TimeScale`].
+///
+/// Everything that moves the character should use this instead of the [`Time`] directly.
+fn scaled_delta_secs(time: &Time, time_scale: Option<&TimeScale>) -> f32 {
+    time.delta_secs() * time_scale.map_or(1.0, |scale| scale.0)
+}
+
+/// Scales the delta time used to move a [`Character`], allowing individual characters to move in slow-motion.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// The full state of a [`Character`], used to attach exact reproduction states to bug reports.
+#[derive(Reflect, Debug)]
+struct CharacterSnapshot {
+    velocity: Vec3,
+    ground: Option<Ground>,
+    previous_ground: Option<Ground>,
+    wall: Option<Dir3>,
+    up: Dir3,
+    config: MoveAndSlideConfig,
+    movement_config: MovementConfig,
+    transform: Transform,
+    /// The transform of the camera targeting the character, the movement direction is relative to it.
+    camera_transform: Option<Transform>,
+}
+
+fn dump_character_state(
+    trigger: Trigger<Fired<DumpState>>,
+    characters: Query<(
+        &Character,
+        &MoveAndSlideConfig,
+        &MovementConfig,
+        &Transform,
+        Option<&TargetedBy>,
+    )>,
+    cameras: Query<&Transform, With<MainCamera>>,
+    type_registry: Res<AppTypeRegistry>,
+) {
+    let Ok((character, config, movement_config, transform, targeted_by)) =
+        characters.get(trigger.target())
+    else {
+        return;
+    };
+
+    let snapshot = CharacterSnapshot {
+        velocity: character.velocity,
+        ground: character.ground,
+        previous_ground: character.previous_ground,
+        wall: character.wall,
+        up: character.up,
+        config: *config,
+        movement_config: *movement_config,
+        transform: *transform,
+        camera_transform: targeted_by
+            .and_then(|targeted_by| cameras.iter_many(targeted_by.iter()).next())
+            .copied(),
+    };
+
+    let type_registry = type_registry.read();
+    let serializer = TypedReflectSerializer::new(&snapshot, &type_registry);
+
+    match ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default()) {
+        Ok(state) => info!("Character state of {}:\n{state}", trigger.target()),
+        Err(err) => error!("Failed to serialize the character state: {err}"),
+    }
+}
+
+fn jump_input(
+    mut query: Query<
+        (
+            &mut Character,
+            &MovementConfig,
+            &Actions<DefaultContext>,
+            Option<&TargetedBy>,
+        ),
+        Without<InputFrozen>,
+    >,
+    fly_cameras: Query<(), With<FlyingCamera>>,
+) {
+    for (mut character, config, actions, targeted_by) in &mut query {
+        if actions.state::<Jump>().unwrap_or_default() != ActionState::Fired
+            || input_consumed_by_fly_camera(targeted_by, &fly_cameras)
+        {
+            continue;
+        }
+
+        match character.grounded_or_coyote() {
+            true => character.jump(config.jump_impulse),
+            false => character.request_jump(),
+        }
+    }
+}
+
+fn release_jump(
+    trigger: Trigger<Fired<ReleaseJump>>,
+    mut query: Query<(&mut Character, &MovementConfig, Option<&TargetedBy>), Without<InputFrozen>>,
+    fly_cameras: Query<(), With<FlyingCamera>>,
+) {
+    if let Ok((mut character, config, targeted_by)) = query.get_mut(trigger.target())
+        && !input_consumed_by_fly_camera(targeted_by, &fly_cameras)
+    {
+        character.cut_jump(config.jump_cut_multiplier);
+    }
+}
+
+/// Returns `true` if a [`FlyingCamera`] targeting the character consumes it's movement input.
+///
+/// Only the input is ignored, the character keeps falling and colliding while the camera flies around.
+pub fn input_consumed_by_fly_camera(
+    targeted_by: Option<&TargetedBy>,
+    fly_cameras: &Query<(), With<FlyingCamera>>,
+) -> bool {
+    targeted_by.is_some_and(|targeted_by| {
+        targeted_by
+            .iter()
+            .any(|camera| fly_cameras.contains(camera))
+    })
+}
+
+/// Returns the transform of the `entity` including any changes since the last transform propagation.
+fn current_global_transform(
+    entity: Entity,
+    transforms: &Query<(&Transform, Option<&ChildOf>), Without<Character>>,
+) -> Option<GlobalTransform> {
+    let (transform, child_of) = transforms.get(entity).ok()?;
+
+    Some(match child_of {
+        Some(child_of) => {
+            current_global_transform(child_of.parent(), transforms)?.mul_transform(*transform)
+        }
+        None => GlobalTransform::from(*transform),
+    })
+}
+
+fn dash_input(
+    trigger: Trigger<Fired<Dash>>,
+    mut query: Query<
+        (
+            &mut Character,
+            &CharacterInput,
+            &MovementConfig,
+            Option<&TargetedBy>,
+        ),
+        Without<InputFrozen>,
+    >,
+    fly_cameras: Query<(), With<FlyingCamera>>,
+) {
+    if let Ok((mut character, input, config, targeted_by)) = query.get_mut(trigger.target())
+        && !input_consumed_by_fly_camera(targeted_by, &fly_cameras)
+        && let Ok(direction) = Dir3::new(input.wish_dir)
+    {
+        character.dash(direction, config.dash_speed);
+    }
+}
+
+/// Carries characters along with the platform they are standing on.
+///
+/// Parented characters are carried by their parent instead.
+fn platform_movement(
+    spatial_query: SpatialQuery,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut Character,
+            &MoveAndSlideConfig,
+            &Collider,
+            &CharacterFilter,
+        ),
+        Without<ChildOf>,
+    >,
+    platforms: Query<(&Transform, Option<&ChildOf>), Without<Character>>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut character, config, collider, filter) in &mut query {
+        let sample_point = match character.platform_sample_origin {
+            PlatformSampleOrigin::Center => transform.translation,
+            // Only capsules and cylinders know where their bottom is, other shapes use the center
+            PlatformSampleOrigin::Feet => {
+                transform.translation
+                    - character.up
+                        * CharacterShape::from_collider(collider)
+                            .map_or(0.0, |shape| shape.height() / 2.0)
+            }
+            PlatformSampleOrigin::Contact => character
+                .ground
+                .or(character.previous_ground)
+                .map_or(transform.translation, |ground| ground.point),
+        };
+
+        // The platforms are sampled every fixed update, so this is the motion since the last one.
+        // Platforms animated in `Update` are picked up by the next fixed update, however many frames that takes
+        let platform_transform = character.platform_transform;
+        let platform_motion = |entity| match platform_transform {
+            Some((previous_entity, prev_platform_transform)) if previous_entity == entity => {
+                current_global_transform(entity, &platforms).map_or(
+                    Vec3::ZERO,
+                    |platform_transform| {
+                        motion_on_point(sample_point, &platform_transform, &prev_platform_transform)
+                    },
+                )
+            }
+            _ => Vec3::ZERO,
+        };
+
+        match (character.ground, character.previous_ground) {
+            // Just landed, the platform moved before we were standing on it so there's nothing to follow yet
+            (Some(_), None) => {}
+            // Currently on the platform, follow it's movement
+            (Some(ground), ..) => {
+                let platform_motion = platform_motion(ground.entity);
+
+                // The platform is moving too fast to follow, detach without inheriting it's velocity.
+                // The limit is a speed to not depend on the fixed timestep
+                let max_carry_distance = MAX_PLATFORM_CARRY_SPEED * time.delta_secs();
+                if platform_motion.length_squared() > max_carry_distance.powi(2) {
+                    character.ground = None;
+                    character.previous_ground = None;
+                    continue;
+                }
+
+                // Sweep in the platform movement direction to avoid passing through walls
+                if let Ok((direction, max_distance)) = Dir3::new_and_length(platform_motion) {
+                    let safe_distance = sweep_check(
+                        collider,
+                        config.epsilon,
+                        config.ignore_origin_penetration,
+                        transform.translation,
+                        direction,
+                        max_distance,
+                        transform.rotation,
+                        &spatial_query,
+                        &filter.0,
+                    )
+                    .map(|(d, _)| d)
+                    .unwrap_or(max_distance);
+
+                    transform.translation += direction * safe_distance;
+                };
+
+                // Turn along with the platform, only about `up` so the character stays upright
+                if character.inherit_platform_rotation
+                    && let Some((previous_entity, prev_platform_transform)) = platform_transform
+                    && previous_entity == ground.entity
+                    && let Some(platform_transform) =
+                        current_global_transform(ground.entity, &platforms)
+                {
+                    let delta = platform_transform.rotation()
+                        * prev_platform_transform.rotation().inverse();
+                    let max_yaw = character.max_platform_angular_speed * time.delta_secs();
+                    let yaw = twist_angle(delta, character.up).clamp(-max_yaw, max_yaw);
+
+                    transform.rotation =
+                        Quat::from_axis_angle(*character.up, yaw) * transform.rotation;
+                }
+            }
+            // Left the platform, inherit the platform velocity
+            (None, Some(previous_ground)) if time.delta_secs() > 0.0 => {
+                let mut platform_velocity =
+                    platform_motion(previous_ground.entity) / time.delta_secs();
+
+                if character.inherit_horizontal_platform_velocity_only && !character.jumped {
+                    platform_velocity = platform_velocity.reject_from_normalized(*character.up);
+                }
+
+                // Avoid launching the character when leaving fast platforms
+                let max_speed = character.max_inherited_platform_speed;
+                character.velocity += (platform_velocity * character.platform_inherit_scale)
+                    .clamp_length_max(max_speed);
+            }
+            _ => {}
+        }
+
+        character.previous_ground = character.ground;
+        character.platform_transform = character.ground.and_then(|ground| {
+            current_global_transform(ground.entity, &platforms)
+                .map(|platform_transform| (ground.entity, platform_transform))
+        });
+    }
+}
+
+type AccelerateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static Actions<DefaultContext>>,
+        Option<&'static Actions<FlyCameraContext>>,
+        Option<&'static TargetedBy>,
+        &'static mut Transform,
+        &'static mut Character,
+        &'static mut CharacterInput,
+        &'static MoveAndSlideConfig,
+        (&'static Collider, &'static StepCollider),
+        &'static CharacterFilter,
+        &'static MovementConfig,
+        &'static CombinedMovementModifier,
+        Option<&'static TimeScale>,
+        Option<&'static LandingMagnetism>,
+        Has<Sensor>,
+        Has<InputFrozen>,
+    ),
+>;
+
+fn accelerate(
+    mut q_kcc: AccelerateQuery,
+    cameras: Query<&Transform, (With<MainCamera>, Without<Character>)>,
+    fly_cameras: Query<(), With<FlyingCamera>>,
+    surfaces: Query<&SurfaceWalkability>,
+    excluded: Res<ExcludedColliders>,
+    gravity: Res<CharacterGravity>,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+) {
+    for (
+        actions,
+        fly_actions,
+        targeted_by,
+        mut transform,
+        mut character,
+        mut character_input,
+        config,
+        (collider, step_collider),
+        filter,
+        movement_config,
+        movement_modifier,
+        time_scale,
+        landing_magnetism,
+        has_sensor,
+        input_frozen,
+    ) in &mut q_kcc
+    {
+        let movement_config = &movement_modifier.0.apply(movement_config);
+        let delta_secs = scaled_delta_secs(&time, time_scale);
+        let was_grounded = character.grounded();
+        let dynamic_body_response = character.dynamic_body_response;
+        let walkable_angle = |entity| {
+            character_walkable_angle(
+                entity,
+                &surfaces,
+                &excluded,
+                dynamic_body_response,
+                movement_config.walkable_angle_hysteresis,
+                was_grounded,
+            )
+        };
+        // Stepping always lands on a new surface, so use the strictest walkable angle to make sure
+        // we can't step on surfaces that are nearly excactly the walkable angle of the character
+        let step_walkable_angle = |entity| {
+            character_walkable_angle(
+                entity,
+                &surfaces,
+                &excluded,
+                dynamic_body_response,
+                movement_config.walkable_angle_hysteresis,
+                false,
+            )
+        };
+
+        // Stand upright against the gravity unless overridden, keep the current `up` in zero gravity
+        let up = character
+            .up_override
+            .or_else(|| Dir3::new(-gravity.0).ok())
+            .unwrap_or(character.up);
+        character.recompute_up(up);
+
+        // Used to find out how far the character actually moved this update
+        character.start_translation = transform.translation;
+        character.step_height = 0.0;
+        character.start_velocity = character.velocity;
+
+        // Characters without input (e.g. NPCs), with frozen input or with a flying camera don't move on their own
+        let ignore_input = input_frozen || input_consumed_by_fly_camera(targeted_by, &fly_cameras);
+        let actions = actions.filter(|_| !ignore_input);
+        let fly_actions = fly_actions.filter(|_| !ignore_input);
+
+        // Get the raw 2D input vector
+        let input_vec = actions
+            .and_then(|actions| actions.value::<input::Move>().ok())
+            .unwrap_or_default();
+
+        // Only swimming characters can move up and down on their own
+        let vertical_input = match character.movement_mode {
+            MovementMode::Walking => 0.0,
+            MovementMode::Swimming => fly_actions
+                .and_then(|actions| actions.value::<input::Fly>().ok())
+                .unwrap_or_default(),
+        };
+
+        // Move relative to the camera of this character, or the character itself if it has none
+        let camera_rotation = targeted_by
+            .and_then(|targeted_by| cameras.iter_many(targeted_by.iter()).next())
+            .map_or(transform.rotation, |camera_transform| {
+                camera_transform.rotation
+            });
+
+        // Flatten the camera axes on the plane of the character's `up`, there's no global up to take the yaw from
+        let forward = (camera_rotation * Vec3::NEG_Z)
+            .reject_from(*character.up)
+            .normalize_or_zero();
+        let right = (camera_rotation * Vec3::X)
+            .reject_from(*character.up)
+            .normalize_or_zero();
+
+        let direction = right * input_vec.x + forward * input_vec.y + character.up * vertical_input;
+
+        *character_input = CharacterInput {
+            wish_dir: direction.normalize_or_zero(),
+            wish_speed: movement_config.movement_speed * direction.length().min(1.0),
+            jump: actions.is_some_and(|actions| {
+                actions.state::<Jump>().unwrap_or_default() == ActionState::Fired
+            }),
+            crouch: actions.is_some_and(|actions| {
+                actions.state::<input::Crouch>().unwrap_or_default() == ActionState::Fired
+            }),
+        };
+
+        // Swim up off the ground, otherwise the ascent would be projected on the ground
+        if vertical_input > 0.0 {
+            character.ground = None;
+        }
+
+        let max_acceleration = match character.ground {
+            Some(ground) => {
+                let friction = friction(character.velocity, movement_config.friction, delta_secs);
+                character.velocity += friction;
+
+                // Friction only approaches zero, so snap to a stop when there's no input
+                if direction == Vec3::ZERO
+                    && character.velocity.length_squared() < movement_config.stop_speed.powi(2)
+                {
+                    character.velocity = Vec3::ZERO;
+                }
+
+                // Slide down steep slopes, gentle ones hold the character in place
+                character.velocity = apply_slope_gravity(
+                    character.velocity,
+                    *ground.normal,
+                    character.up,
+                    gravity.0.length() * movement_config.gravity_scale,
+                    movement_config.slope_hold_angle,
+                    movement_config.max_slope_slide_speed,
+                    delta_secs,
+                );
+
+                movement_config.ground_acceleration
+            }
+            // Swimming uses friction as drag instead of gravity
+            None if character.movement_mode == MovementMode::Swimming => {
+                let friction = friction(character.velocity, movement_config.friction, delta_secs);
+                character.velocity += friction;
+
+                movement_config.air_acceleration
+            }
+            None => {
+                let gravity = gravity.0.length() * movement_config.gravity_scale;
+
+                // Slide down steep slopes instead of getting stuck on them
+                let steep_slope = character
+                    .contacts
+                    .iter()
+                    .find(|contact| {
+                        contact.kind == SurfaceKind::Wall && contact.normal.dot(*character.up) > 0.0
+                    })
+                    .map(|contact| contact.normal);
+
+                // Apply gravity when not grounded, a steep slope holds up the part of it going into the slope
+                character.velocity = match steep_slope {
+                    Some(normal) => apply_slope_slide(
+                        character.velocity,
+                        *normal,
+                        character.up,
+                        gravity,
+                        delta_secs,
+                    ),
+                    None => character.velocity - character.up * gravity * delta_secs,
+                };
+
+                // Pull descending characters onto ground within reach
+                if let Some(magnetism) = landing_magnetism
+                    && character.velocity.dot(*character.up) < magnetism.activation_speed
+                    && ground_check(
+                        collider,
+                        *config,
+                        transform.translation,
+                        character.up,
+                        transform.rotation,
+                        &spatial_query,
+                        &filter.0,
+                        GroundCheckParams::fixed(magnetism.range),
+                        walkable_angle,
+                    )
+                    .is_some()
+                {
+                    let up = character.up;
+                    character.velocity -= up * magnetism.strength * delta_secs;
+                }
+
+                // Use a distinct acceleration for adjusting the trajectory along a wall
+                match character.wall_running() {
+                    true => movement_config.wall_run_acceleration,
+                    false => movement_config.air_acceleration,
+                }
+            }
+        };
+
+        // accelerate in the movement direction, airborne characters can only turn so fast
+        let mut move_accel =
+            match character.grounded() || character.movement_mode == MovementMode::Swimming {
+                true => acceleration(
+                    character.velocity,
+                    direction,
+                    max_acceleration,
+                    movement_config.movement_speed,
+                    delta_secs,
+                ),
+                false => air_accelerate(
+                    character.velocity,
+                    direction,
+                    character.up,
+                    max_acceleration,
+                    movement_config.movement_speed,
+                    movement_config.air_turn_rate,
+                    delta_secs,
+                ),
+            };
+
+        // Sensors and phasing characters don't collide, so there's nothing to project the acceleration on
+        if has_sensor || character.phasing() {
+            character.velocity += move_accel;
+
+            continue;
+        }
+
+        // We need to store the new ground for the ground check to work properly
+        let mut new_ground = None;
+        let mut new_wall = None;
+
+        if let Some(ground) = character.ground {
+            // Project acceleration on the ground plane
+            move_accel = project_motion_on_ground(move_accel, *ground.normal, character.up);
         }
 
         // Sweep in the movement direction to find a plane to project acceleration on
         // This is a seperate step because trying to do this in the `move_and_slide` callback
         // results in "sticking" to the wall rather than sliding down at the expected rate
-        if let Ok((direction, max_distance)) = Dir3::new_and_length(move_accel * time.delta_secs())
-        {
-            if let Some((safe_distance, hit)) = sweep_check(
+        if let Ok((direction, max_distance)) = Dir3::new_and_length(move_accel * delta_secs)
+            && let Some((safe_distance, hit)) = sweep_check(
                 collider,
-                character.config.epsilon,
+                config.epsilon,
+                config.ignore_origin_penetration,
                 transform.translation,
                 direction,
                 max_distance,
                 transform.rotation,
                 &spatial_query,
                 &filter.0,
+            )
+        {
+            // Move to the hit point
+            transform.translation += direction * safe_distance;
+
+            if let Some(ground) = Ground::new_if_walkable(
+                hit.entity,
+                hit.normal1,
+                hit.point1,
+                character.up,
+                walkable_angle(hit.entity),
             ) {
-                // Move to the hit point
-                transform.translation += direction * safe_distance;
+                new_ground = Some(ground);
+
+                // If the ground is walkable, project motion on ground plane
+                move_accel = project_motion_on_ground(move_accel, hit.normal1, character.up);
+            } else if character.enable_step_up
+                && let Some(step_result) = try_step_up_on_hit(
+                    step_collider.0.as_ref().unwrap_or(collider),
+                    transform.translation,
+                    transform.rotation,
+                    character.up,
+                    &hit,
+                    direction,
+                    max_distance - safe_distance,
+                    movement_config.max_step_height,
+                    config.epsilon,
+                    &spatial_query,
+                    &filter.0,
+                    step_walkable_angle,
+                )
+            {
+                new_ground = Some(step_result.ground);
+                character.stepped_up = true;
+                character.step_height += step_result.height;
+
+                // Step up
+                transform.translation = step_result.translation;
+            } else {
+                // Keep track of the wall when pushing into it mid-air
+                if !character.grounded() {
+                    new_wall = Dir3::new(hit.normal1).ok();
+                }
+
+                // If the ground is not walkable, project motion on wall plane
+                move_accel = project_motion_on_wall(move_accel, hit.normal1, character.up);
+            }
+        }
+
+        character.velocity += move_accel;
+
+        // Carry over the ground and wall found while accelerating to the integration
+        character.new_ground = new_ground;
+        character.new_wall = new_wall;
+    }
+}
+
+fn movement(
+    mut q_kcc: Query<(
+        Entity,
+        &mut Transform,
+        &mut Character,
+        &MoveAndSlideConfig,
+        &Collider,
+        &StepCollider,
+        &CharacterFilter,
+        &MovementConfig,
+        Option<&TimeScale>,
+        Has<Sensor>,
+    )>,
+    mut landed: EventWriter<Landed>,
+    collision_layers: Query<&CollisionLayers>,
+    colliders: Query<(&Collider, &Position, &Rotation)>,
+    collider_of: Query<&ColliderOf>,
+    mut bodies: Query<(&RigidBody, &mut LinearVelocity)>,
+    mut budget: ResMut<CharacterUpdateBudget>,
+    surfaces: Query<&SurfaceWalkability>,
+    excluded: Res<ExcludedColliders>,
+    one_way_platforms: Query<&GlobalTransform, With<OneWayPlatform>>,
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+) {
+    let count = q_kcc.iter().len();
+    let collider_transform = |entity| {
+        colliders
+            .get(entity)
+            .ok()
+            .map(|(collider, position, rotation)| (collider, position.0, rotation.0))
+    };
+
+    for (
+        index,
+        (
+            entity,
+            mut transform,
+            mut character,
+            config,
+            collider,
+            step_collider,
+            filter,
+            movement_config,
+            time_scale,
+            has_sensor,
+        ),
+    ) in q_kcc.iter_mut().enumerate()
+    {
+        let delta_secs = scaled_delta_secs(&time, time_scale);
+        let was_grounded = character.grounded();
+        let dynamic_body_response = character.dynamic_body_response;
+        let walkable_angle = |entity| {
+            character_walkable_angle(
+                entity,
+                &surfaces,
+                &excluded,
+                dynamic_body_response,
+                movement_config.walkable_angle_hysteresis,
+                was_grounded,
+            )
+        };
+        // Stepping always lands on a new surface, so use the strictest walkable angle to make sure
+        // we can't step on surfaces that are nearly excactly the walkable angle of the character
+        let step_walkable_angle = |entity| {
+            character_walkable_angle(
+                entity,
+                &surfaces,
+                &excluded,
+                dynamic_body_response,
+                movement_config.walkable_angle_hysteresis,
+                false,
+            )
+        };
+        let velocity = character
+            .integrator
+            .velocity(character.start_velocity, character.velocity);
+
+        // The phase runs out even for sensors
+        let phasing = character.phasing();
+        character.phase_time = (character.phase_time - delta_secs).max(0.0);
+
+        // Move straight through everything while phasing
+        if phasing {
+            transform.translation += velocity * delta_secs;
+
+            // Keep following the ground while passing through walls, walking off of it still makes the character fall
+            let ground_hit = match character.grounded() {
+                true => ground_check(
+                    collider,
+                    *config,
+                    transform.translation,
+                    character.up,
+                    transform.rotation,
+                    &spatial_query,
+                    &filter.0,
+                    GroundCheckParams::fixed(GROUND_CHECK_DISTANCE),
+                    walkable_angle,
+                ),
+                false => None,
+            };
+
+            match ground_hit {
+                Some((safe_distance, ground)) => {
+                    transform.translation -= character.up * safe_distance;
+                    character.ground = Some(ground);
+                }
+                None => character.ground = None,
+            }
+
+            // We might have ended up inside of something, push out of it
+            if !character.phasing() {
+                transform.translation = depenetrate(
+                    &spatial_query,
+                    collider,
+                    transform.translation,
+                    transform.rotation,
+                    *config,
+                    &filter.0,
+                    |_| true,
+                    collider_transform,
+                );
+            }
+
+            character.motion = transform.translation - character.start_translation;
+            character.contact_count = 0;
+            character.contacts.clear();
+
+            continue;
+        }
+
+        // We can skip everything if the character has a sensor component or is out of budget this frame
+        if has_sensor || !budget.is_full_update(index, count) {
+            match Dir3::new_and_length(velocity * delta_secs) {
+                // Characters out of budget still sweep once, so they can't pass through walls
+                Ok((direction, max_distance)) if !has_sensor => {
+                    if let Some((safe_distance, hit)) = sweep_check(
+                        collider,
+                        config.epsilon,
+                        config.ignore_origin_penetration,
+                        transform.translation,
+                        direction,
+                        max_distance,
+                        transform.rotation,
+                        &spatial_query,
+                        &filter.0,
+                    ) {
+                        transform.translation += direction * safe_distance;
+
+                        // Stop moving into the surface, the next full update slides along it
+                        let into_surface = character.velocity.dot(hit.normal1).min(0.0);
+                        character.velocity -= hit.normal1 * into_surface;
+                    } else {
+                        transform.translation += direction * max_distance;
+                    }
+                }
+                Ok((direction, max_distance)) => {
+                    transform.translation += direction * max_distance;
+                }
+                Err(_) => {}
+            }
+
+            character.motion = transform.translation - character.start_translation;
+            character.contact_count = 0;
+            character.contacts.clear();
+
+            continue;
+        }
+
+        let mut new_ground = character.new_ground.take();
+        let mut new_wall = character.new_wall.take();
+
+        // Dynamic bodies that were hit while pushing, along with the velocity to push them with
+        let mut pushes = Vec::new();
+
+        character.contacts.clear();
+
+        let move_result = move_and_slide(
+            &spatial_query,
+            collider,
+            transform.translation,
+            velocity,
+            transform.rotation,
+            *config,
+            &filter.0,
+            delta_secs,
+            MoveAndSlideContext {
+                sweep_collider: None,
+                memberships: |entity| {
+                    collision_layers
+                        .get(entity)
+                        .copied()
+                        .unwrap_or_default()
+                        .memberships
+                },
+                should_collide: |hit, velocity| {
+                    let body = collider_of
+                        .get(hit.entity)
+                        .map_or(hit.entity, |collider_of| collider_of.body);
+
+                    // Only land on top of one-way platforms, moving up through them or hitting their sides is ignored
+                    match one_way_platforms.get(body) {
+                        Ok(platform) => {
+                            velocity.dot(*platform.up()) <= 0.0
+                                && hit.normal1.dot(*platform.up()) > 0.0
+                        }
+                        Err(_) => true,
+                    }
+                },
+            },
+            collider_transform,
+            |hit| {
+                if let Ok(normal) = Dir3::new(hit.hit_data.normal1) {
+                    let kind = if is_walkable(
+                        *normal,
+                        character.up,
+                        walkable_angle(hit.hit_data.entity),
+                    ) {
+                        SurfaceKind::Ground
+                    } else if is_ceiling(*normal, character.up, PI / 2.0) {
+                        SurfaceKind::Ceiling
+                    } else {
+                        SurfaceKind::Wall
+                    };
+
+                    // Sliding along a surface hits it again in the following substeps
+                    let already_touched = character.contacts.iter().any(|contact| {
+                        contact.entity == hit.hit_data.entity && contact.kind == kind
+                    });
+
+                    if !already_touched {
+                        character.contacts.push(ContactInfo {
+                            entity: hit.hit_data.entity,
+                            normal,
+                            point: hit.hit_data.point1,
+                            kind,
+                        });
+                    }
+                }
+
+                let body = collider_of
+                    .get(hit.hit_data.entity)
+                    .map_or(hit.hit_data.entity, |collider_of| collider_of.body);
+                let dynamic = bodies
+                    .get(body)
+                    .is_ok_and(|(rigid_body, _)| rigid_body.is_dynamic());
+
+                // Everything that's not dynamic is treated as static geometry
+                let response = match dynamic {
+                    true => character.dynamic_body_response,
+                    false => DynamicBodyResponse::StepOn,
+                };
+
+                if response == DynamicBodyResponse::Push {
+                    pushes.push((body, hit.velocity.project_onto(hit.hit_data.normal1)));
+                }
+
+                if response == DynamicBodyResponse::StepOn
+                    && let Some(ground) = Ground::new_if_walkable(
+                        hit.hit_data.entity,
+                        hit.hit_data.normal1,
+                        hit.hit_data.point1,
+                        character.up,
+                        walkable_angle(hit.hit_data.entity),
+                    )
+                {
+                    new_ground = Some(ground);
+
+                    // Avoid sliding down slopes when just landing
+                    if !character.grounded() {
+                        *hit.velocity = project_motion_on_ground(
+                            *hit.velocity,
+                            hit.hit_data.normal1,
+                            character.up,
+                        );
+
+                        character.velocity = project_motion_on_ground(
+                            character.velocity,
+                            hit.hit_data.normal1,
+                            character.up,
+                        );
+                    }
+
+                    return true;
+                }
+
+                let grounded = character.grounded() || new_ground.is_some();
+
+                // In order to try step up we need to be grounded and hitting a "wall".
+                if grounded
+                    && character.enable_step_up
+                    && response == DynamicBodyResponse::StepOn
+                    && let Some(step_result) = try_step_up_on_hit(
+                        step_collider.0.as_ref().unwrap_or(collider),
+                        *hit.translation,
+                        transform.rotation,
+                        character.up,
+                        &hit.hit_data,
+                        hit.direction,
+                        hit.remaining_motion,
+                        movement_config.max_step_height,
+                        config.epsilon,
+                        &spatial_query,
+                        &filter.0,
+                        step_walkable_angle,
+                    )
+                {
+                    new_ground = Some(step_result.ground);
+                    character.stepped_up = true;
+                    character.step_height += step_result.height;
+
+                    // Subtract the stepped distance from remaining time to avoid moving further
+                    hit.consume_motion(step_result.move_distance);
+
+                    // We need to override the translation here because the we stepped up
+                    *hit.translation = step_result.translation;
+
+                    // Successfully stepped, don't slide this iteration
+                    return false;
+                }
+
+                // Facing down means we hit a ceiling (or an overhang)
+                let ceiling = is_ceiling(hit.hit_data.normal1, character.up, PI / 2.0);
+
+                // Slide vleocity along walls
+                match grounded {
+                    // Slide along sloped ceilings, pushing us into the ground instead of stopping
+                    true if ceiling => {
+                        character.velocity =
+                            project_motion_on_ceiling(character.velocity, hit.hit_data.normal1);
+
+                        *hit.velocity =
+                            project_motion_on_ceiling(*hit.velocity, hit.hit_data.normal1)
+                    }
+                    // Avoid sliding up walls when grounded
+                    true => {
+                        character.velocity = project_motion_on_wall(
+                            character.velocity,
+                            hit.hit_data.normal1,
+                            character.up,
+                        );
+
+                        *hit.velocity = project_motion_on_wall(
+                            *hit.velocity,
+                            hit.hit_data.normal1,
+                            character.up,
+                        )
+                    }
+                    false => {
+                        match ceiling {
+                            // Bumping into a ceiling cancels the jump but keeps the horizontal velocity
+                            true => {
+                                let upward = character.velocity.dot(*character.up).max(0.0);
+                                let up = character.up;
+                                character.velocity -= up * upward;
+                            }
+                            false => {
+                                let normal = hit.hit_data.normal1;
+
+                                // Landing on a steep slope turns the fall into a slide down the slope instead of stopping
+                                if character.wall.is_none() {
+                                    let slide = steep_landing_velocity(
+                                        character.velocity,
+                                        normal,
+                                        character.up,
+                                        walkable_angle(hit.hit_data.entity),
+                                        movement_config.steep_landing_retention,
+                                    );
+                                    character.velocity += slide;
+                                }
+
+                                new_wall = Dir3::new(normal).ok();
+                            }
+                        }
+
+                        character.velocity = character.velocity.reject_from(hit.hit_data.normal1)
+                    }
+                };
+
+                true
+            },
+        );
+
+        transform.translation = move_result.new_translation;
+
+        // Push the bodies at least as fast as the character was moving into them
+        for (body, push_velocity) in pushes {
+            if let Ok((_, mut linear_velocity)) = bodies.get_mut(body) {
+                let direction = push_velocity.normalize_or_zero();
+                let missing_speed = push_velocity.length() - linear_velocity.dot(direction);
+
+                linear_velocity.0 += direction * missing_speed.max(0.0);
+            }
+        }
+        character.contact_count = move_result.contact_count;
+
+        let run_ground_check = match character.ground_check_override.take() {
+            Some(GroundCheckOverride::Force) => true,
+            Some(GroundCheckOverride::Skip) => false,
+            None => character.grounded(),
+        };
+
+        let ground_hit = match run_ground_check {
+            true => ground_check(
+                collider,
+                *config,
+                transform.translation,
+                character.up,
+                transform.rotation,
+                &spatial_query,
+                &filter.0,
+                GroundCheckParams {
+                    floor_check_distance: GROUND_CHECK_DISTANCE,
+                    velocity: character.velocity,
+                    delta_time: delta_secs,
+                    speed_scaled_extra: GROUND_CHECK_SPEED_SCALE,
+                },
+                walkable_angle,
+            ),
+            false => None,
+        };
+
+        // Check if the previous ground is still there and snap to it
+        if let Some((safe_distance, ground)) = ground_hit {
+            // Don't get pulled down over the edge of a ledge, the center has to be above the ground.
+            // Only capsules and cylinders know where their bottom is, other shapes are always pulled down
+            let over_ledge = safe_distance > config.epsilon
+                && CharacterShape::from_collider(collider).is_some_and(|shape| {
+                    is_ledge_edge(
+                        &spatial_query,
+                        transform.translation,
+                        character.up,
+                        &ground,
+                        shape.radius(),
+                        shape.height() / 2.0 + safe_distance,
+                        &filter.0,
+                    )
+                });
+
+            if !over_ledge {
+                transform.translation -= character.up * safe_distance.min(MAX_GROUND_SNAP_DISTANCE);
+                new_ground = Some(ground);
+            }
+        }
+        // Walking off the edge of a step, snap down onto the next one instead of floating off the edge
+        else if run_ground_check
+            && character.velocity.dot(*character.up) <= 0.0
+            && let Some((safe_distance, ground)) = try_step_down(
+                &spatial_query,
+                collider,
+                transform.translation,
+                transform.translation - character.start_translation,
+                transform.rotation,
+                character.up,
+                movement_config.max_step_height,
+                config.epsilon,
+                &filter.0,
+                walkable_angle,
+            )
+        {
+            transform.translation -= character.up * safe_distance;
+            new_ground = Some(ground);
+        }
+
+        // let h = character
+        //     .velocity
+        //     .reject_from_normalized(*character.up)
+        //     .length();
+        // let v = character
+        //     .velocity
+        //     .project_onto_normalized(*character.up)
+        //     .length();
+        // let all = character.velocity.length();
+        // dbg!([h, v, all]);
+
+        // Stay grounded for one update after stepping up, the ground check can miss the next step in between
+        if new_ground.is_none() && character.step_grace {
+            new_ground = character.ground;
+        }
+        character.step_grace = std::mem::take(&mut character.stepped_up);
+
+        let just_landed = !character.grounded() && new_ground.is_some();
+
+        if just_landed {
+            character.jumped = false;
+
+            let impact_speed = (-velocity.dot(*character.up)).max(0.0);
+
+            landed.write(Landed::new(
+                entity,
+                impact_speed,
+                movement_config.max_land_speed,
+            ));
+        }
+
+        // The ground might not have been hit while moving, e.g. when standing still
+        if let Some(ground) = new_ground {
+            let already_touched = character.contacts.iter().any(|contact| {
+                contact.entity == ground.entity && contact.kind == SurfaceKind::Ground
+            });
+
+            if !already_touched {
+                character.contacts.push(ContactInfo {
+                    entity: ground.entity,
+                    normal: ground.normal,
+                    point: ground.point,
+                    kind: SurfaceKind::Ground,
+                });
+            }
+        }
+
+        // The coyote time only starts running out after leaving the ground
+        character.coyote_time = match new_ground {
+            Some(_) => movement_config.coyote_time,
+            None => (character.coyote_time - delta_secs).max(0.0),
+        };
+
+        // Update the ground
+        character.ground = new_ground;
+        character.wall = new_wall;
+        character.motion = transform.translation - character.start_translation;
+
+        // Perform a jump requested shortly before landing, requests that are too old are discarded
+        character.jump_request_age = character
+            .jump_request_age
+            .map(|age| age + delta_secs)
+            .filter(|&age| age <= movement_config.jump_buffer);
+
+        if just_landed && character.jump_request_age.is_some() {
+            character.jump(movement_config.jump_impulse);
+        }
+    }
+
+    budget.advance(count);
+}
+
+/// Returns the walkable angle of the surface of `entity`, this is the [`WALKABLE_ANGLE`] unless it has a [`SurfaceWalkability`].
+fn surface_walkable_angle(surfaces: &Query<&SurfaceWalkability>, entity: Entity) -> f32 {
+    surfaces
+        .get(entity)
+        .map_or(WALKABLE_ANGLE, |surface| surface.0)
+}
+
+/// Returns the walkable angle of `entity` for a character, including the hysteresis `margin` (see [`walkable_angle_with_hysteresis`]).
+///
+/// Dynamic bodies are only walkable for characters that step on them ([`DynamicBodyResponse::StepOn`]),
+/// so the ground checks agree with how the `on_hit` callback responds to them.
+fn character_walkable_angle(
+    entity: Entity,
+    surfaces: &Query<&SurfaceWalkability>,
+    excluded: &ExcludedColliders,
+    dynamic_body_response: DynamicBodyResponse,
+    margin: f32,
+    grounded: bool,
+) -> f32 {
+    if dynamic_body_response != DynamicBodyResponse::StepOn && excluded.dynamic.contains(&entity) {
+        return 0.0;
+    }
+
+    walkable_angle_with_hysteresis(surface_walkable_angle(surfaces, entity), margin, grounded)
+}
+
+struct StepUpResult {
+    translation: Vec3,
+    /// The distance moved forward while stepping.
+    move_distance: f32,
+    /// The height climbed along the up direction.
+    height: f32,
+    ground: Ground,
+}
+
+/// Tries to step up onto whatever was hit using the `step_collider`, see [`StepCollider`].
+///
+/// Obstacles up to `step_height` are climbed, the step has to be walkable by the `walkable_angle` of the character.
+fn try_step_up_on_hit(
+    step_collider: &Collider,
+    translation: Vec3,
+    rotation: Quat,
+    up: Dir3,
+    hit: &ShapeHitData,
+    direction: Dir3,
+    mut step_forward: f32,
+    step_height: f32,
+    epsilon: f32,
+    spatial_query: &SpatialQuery,
+    filter: &SpatialQueryFilter,
+    walkable_angle: impl Fn(Entity) -> f32,
+) -> Option<StepUpResult> {
+    let horizontal_normal = hit.normal1.reject_from_normalized(*up).normalize_or_zero();
+
+    // Only capsules and cylinders know how to step
+    let shape = CharacterShape::from_collider(step_collider)?;
+
+    // Step into the hit normal alil bit, this is necessary for capsule colliders since the normal angle
+    // changes depending on how far out on a ledge the character is standing
+    let inward = shape.ledge_inward(walkable_angle(hit.entity), epsilon);
+
+    // Step a lil bit less forward to account for stepping into the hit normal
+    step_forward = (step_forward - inward).max(0.0);
+
+    let step_motion = direction * step_forward - horizontal_normal * inward;
+
+    let Some((step_translation, hit, height)) = try_climb_step(
+        spatial_query,
+        step_collider,
+        translation,
+        step_motion,
+        rotation,
+        up,
+        step_height + GROUND_CHECK_DISTANCE,
+        epsilon,
+        filter,
+    ) else {
+        // Can't stand here, slide instead
+        return None;
+    };
+
+    let ground = Ground::new_if_walkable(
+        hit.entity,
+        hit.normal1,
+        hit.point1,
+        up,
+        walkable_angle(hit.entity),
+    )?;
+
+    Some(StepUpResult {
+        translation: step_translation,
+        move_distance: step_forward + inward,
+        height,
+        ground,
+    })
+}
+
+/// Like [`acceleration`], but the horizontal velocity is first turned towards the `direction` by at most
+/// `turn_rate * delta` radians, keeping it's speed.
+///
+/// This limits how quickly the direction can change in the air, separately from the speed gain.
+#[must_use]
+fn air_accelerate(
+    velocity: Vec3,
+    direction: impl TryInto<Dir3>,
+    up: Dir3,
+    max_acceleration: f32,
+    target_speed: f32,
+    turn_rate: f32,
+    delta: f32,
+) -> Vec3 {
+    let Ok(direction) = direction.try_into() else {
+        return Vec3::ZERO;
+    };
+
+    let horizontal_velocity = velocity.reject_from_normalized(*up);
+    let wish_dir = Dir3::new(direction.reject_from_normalized(*up));
+
+    // Turn the horizontal velocity towards the input
+    let turned_velocity = match (Dir3::new(horizontal_velocity), wish_dir) {
+        (Ok(current_dir), Ok(wish_dir)) => {
+            let angle = current_dir.angle_between(*wish_dir);
+            let t = match angle > 0.0 {
+                true => (turn_rate * delta / angle).min(1.0),
+                false => 1.0,
+            };
+
+            current_dir.slerp(wish_dir, t) * horizontal_velocity.length()
+        }
+        _ => horizontal_velocity,
+    };
+
+    // Gain speed along the turned velocity, accelerating towards the input would turn it further
+    let turn = turned_velocity - horizontal_velocity;
+    let speed_gain = acceleration(
+        velocity + turn,
+        Dir3::new(turned_velocity).unwrap_or(direction),
+        max_acceleration,
+        target_speed,
+        delta,
+    );
+
+    turn + speed_gain
+}
+
+/// This is a simple example inspired by Quake, users are expected to bring their own logic for acceleration.
+#[must_use]
+fn acceleration(
+    velocity: Vec3,
+    direction: impl TryInto<Dir3>,
+    max_acceleration: f32,
+    target_speed: f32,
+    delta: f32,
+) -> Vec3 {
+    let Ok(direction) = direction.try_into() else {
+        return Vec3::ZERO;
+    };
+
+    // Current speed in the desired direction.
+    let current_speed = velocity.dot(*direction);
+
+    // No acceleration is needed if current speed exceeds target.
+    if current_speed >= target_speed {
+        return Vec3::ZERO;
+    }
+
+    // Clamp to avoid acceleration past the target speed.
+    let accel_speed = f32::min(target_speed - current_speed, max_acceleration * delta);
+
+    direction * accel_speed
+}
+
+/// Constant acceleration in the opposite direction of velocity.
+#[must_use]
+pub fn friction(velocity: Vec3, friction: f32, delta: f32) -> Vec3 {
+    let speed_sq = velocity.length_squared();
+
+    if speed_sq < 1e-4 {
+        return Vec3::ZERO;
+    }
+
+    let factor = f32::exp(-friction / speed_sq.sqrt() * delta);
+
+    -velocity * (1.0 - factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{scene::ScenePlugin, time::TimeUpdateStrategy};
+    use bevy_enhanced_input::prelude::*;
+    use core::{f32::consts::TAU, time::Duration};
+    use examples_common::camera::Targeting;
+
+    #[test]
+    fn wall_running_only_while_airborne_next_to_a_wall() {
+        let mut character = Character::default();
+        assert!(!character.wall_running());
+
+        character.wall = Some(Dir3::X);
+        assert!(character.wall_running());
+
+        character.set_grounded(Some(Ground {
+            entity: Entity::PLACEHOLDER,
+            normal: Dir3::Y,
+            point: Vec3::ZERO,
+        }));
+        assert!(!character.wall_running());
+    }
+
+    #[test]
+    fn movement_modifier_scales_wall_run_acceleration() {
+        let config = MovementConfig::default();
+        let modifier = MovementModifier {
+            accel_scale: 0.5,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            modifier.apply(&config).wall_run_acceleration,
+            config.wall_run_acceleration * 0.5
+        );
+    }
+
+    #[test]
+    fn time_scale_scales_delta_secs() {
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(500));
+
+        assert_eq!(scaled_delta_secs(&time, None), 0.5);
+        assert_eq!(scaled_delta_secs(&time, Some(&TimeScale(0.5))), 0.25);
+        assert_eq!(scaled_delta_secs(&time, Some(&TimeScale(0.0))), 0.0);
+    }
+
+    /// Returns the apex height of a jump with the `impulse` against the `gravity`, integrated with `delta_secs` steps.
+    fn jump_apex(integrator: Integrator, impulse: f32, gravity: f32, delta_secs: f32) -> f32 {
+        let mut height = 0.0;
+        let mut velocity = Vec3::Y * impulse;
+
+        loop {
+            let accelerated = velocity - Vec3::Y * gravity * delta_secs;
+            let step = integrator.velocity(velocity, accelerated).y * delta_secs;
+
+            if step <= 0.0 {
+                return height;
+            }
+
+            height += step;
+            velocity = accelerated;
+        }
+    }
+
+    #[test]
+    fn midpoint_jump_apex_is_independent_of_the_timestep() {
+        let exact = 10.0_f32.powi(2) / (2.0 * 20.0);
+
+        for delta_secs in [1.0 / 20.0, 1.0 / 64.0, 1.0 / 144.0] {
+            let apex = jump_apex(Integrator::Midpoint, 10.0, 20.0, delta_secs);
+            assert!(
+                (apex - exact).abs() < 1e-3,
+                "apex of {apex} at {delta_secs}"
+            );
+        }
+    }
+
+    #[test]
+    fn semi_implicit_jump_apex_depends_on_the_timestep() {
+        let coarse = jump_apex(Integrator::SemiImplicit, 10.0, 20.0, 1.0 / 20.0);
+        let fine = jump_apex(Integrator::SemiImplicit, 10.0, 20.0, 1.0 / 144.0);
+
+        assert!(coarse < fine);
+    }
+
+    #[test]
+    fn landed_impact_strength_is_normalized() {
+        let strength =
+            |impact_speed| Landed::new(Entity::PLACEHOLDER, impact_speed, 20.0).impact_strength;
+
+        assert_eq!(strength(0.0), 0.0);
+        assert_eq!(strength(5.0), 0.25);
+        assert_eq!(strength(20.0), 1.0);
+        assert_eq!(strength(40.0), 1.0);
+    }
+
+    #[test]
+    fn jump_impulse_for_gap_clears_the_gap() {
+        let (gap, gravity, move_speed) = (4.0, 20.0, 8.0);
+        let impulse = Character::jump_impulse_for_gap(gap, gravity, move_speed);
+        let delta_secs = 1.0 / 64.0;
+
+        let mut translation = Vec3::ZERO;
+        let mut velocity = Vec3::new(move_speed, impulse, 0.0);
+
+        // Fly until the character comes back down to the launch height
+        while translation.y >= 0.0 {
+            let accelerated = velocity - Vec3::Y * gravity * delta_secs;
+            translation += Integrator::Midpoint.velocity(velocity, accelerated) * delta_secs;
+            velocity = accelerated;
+        }
+
+        assert!(translation.x >= gap, "landed at {translation}");
+        assert!(
+            translation.x < gap + move_speed * delta_secs * 2.0,
+            "landed at {translation}"
+        );
+        assert!(Character::jump_impulse_for_gap(gap, gravity, 0.0).is_infinite());
+    }
+
+    /// Returns a headless app moving characters with the [`KCCPlugin`] at 64 updates per second.
+    ///
+    /// Characters spawned with [`spawn_character`] walk with the WASD keys, jump with space and crouch with left control.
+    /// Swimming characters with [`Actions<FlyCameraContext>`] swim up with E and down with Q.
+    fn character_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            bevy::input::InputPlugin,
+            EnhancedInputPlugin,
+            // The collider constructors need the mesh and scene assets
+            AssetPlugin::default(),
+            ScenePlugin,
+            PhysicsPlugins::default(),
+            KCCPlugin,
+        ));
+        app.add_input_context::<DefaultContext>()
+            .add_input_context::<FlyCameraContext>()
+            .add_observer(bind_test_actions)
+            .add_observer(bind_test_fly_actions);
+        app.init_asset::<Mesh>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 64.0,
+        )));
+        app.finish();
+        app.cleanup();
+
+        app
+    }
+
+    fn bind_test_actions(
+        trigger: Trigger<OnAdd, Actions<DefaultContext>>,
+        mut players: Query<&mut Actions<DefaultContext>>,
+    ) {
+        if let Ok(mut actions) = players.get_mut(trigger.target()) {
+            actions.bind::<input::Move>().to(Cardinal::wasd_keys());
+            actions
+                .bind::<Jump>()
+                .to(KeyCode::Space)
+                .with_conditions(Press::default());
+            actions.bind::<input::Crouch>().to(KeyCode::ControlLeft);
+        }
+    }
+
+    fn bind_test_fly_actions(
+        trigger: Trigger<OnAdd, Actions<FlyCameraContext>>,
+        mut players: Query<&mut Actions<FlyCameraContext>>,
+    ) {
+        if let Ok(mut actions) = players.get_mut(trigger.target()) {
+            actions.bind::<input::Fly>().to(Bidirectional {
+                positive: KeyCode::KeyE,
+                negative: KeyCode::KeyQ,
+            });
+        }
+    }
+
+    /// Spawns a static box with the full `size` at the `transform`.
+    fn spawn_box(app: &mut App, size: Vec3, transform: Transform) -> Entity {
+        app.world_mut()
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(size.x, size.y, size.z),
+                transform,
+            ))
+            .id()
+    }
+
+    /// Spawns a large floor with it's top at the origin.
+    fn spawn_floor(app: &mut App) -> Entity {
+        spawn_box(
+            app,
+            Vec3::new(40.0, 1.0, 40.0),
+            Transform::from_xyz(0.0, -0.5, 0.0),
+        )
+    }
+
+    /// Spawns the `character` bundle at the `translation`, controlled by the keys of the [`character_app`].
+    ///
+    /// The spatial query only knows about colliders after the first physics step, so this runs two updates
+    /// before anything else happens.
+    fn spawn_character(app: &mut App, translation: Vec3, character: impl Bundle) -> Entity {
+        let entity = app
+            .world_mut()
+            .spawn((
+                character,
+                Transform::from_translation(translation),
+                Actions::<DefaultContext>::default(),
+            ))
+            .id();
+
+        run(app, 2);
+
+        entity
+    }
+
+    /// Runs `frames` updates.
+    fn run(app: &mut App, frames: usize) {
+        for _ in 0..frames {
+            app.update();
+        }
+    }
+
+    /// Holds down the `keys` until they are released.
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+
+        for &key in keys {
+            input.press(key);
+        }
+    }
+
+    fn character(app: &App, entity: Entity) -> &Character {
+        app.world().get::<Character>(entity).unwrap()
+    }
+
+    fn translation(app: &App, entity: Entity) -> Vec3 {
+        app.world().get::<Transform>(entity).unwrap().translation
+    }
+
+    #[test]
+    fn wall_run_acceleration_changes_the_path_along_the_wall() {
+        let mut app = character_app();
+        // Two walls facing -x, far enough apart for the characters not to run into each other
+        for z in [0.0, 20.0] {
+            spawn_box(
+                &mut app,
+                Vec3::new(1.0, 40.0, 10.0),
+                Transform::from_xyz(1.5, 0.0, z),
+            );
+        }
+
+        // Both start falling right next to their wall
+        let slow = spawn_character(&mut app, Vec3::new(0.645, 10.0, 0.0), Character::default());
+        let fast = spawn_character(
+            &mut app,
+            Vec3::new(0.645, 10.0, 20.0),
+            (
+                Character::default(),
+                MovementConfig {
+                    wall_run_acceleration: WALL_RUN_ACCELERATION * 3.0,
+                    ..Default::default()
+                },
+            ),
+        );
+
+        // Run along the wall while pushing into it
+        press(&mut app, &[KeyCode::KeyW, KeyCode::KeyD]);
+        for frame in 0..16 {
+            app.update();
+
+            for entity in [slow, fast] {
+                assert!(
+                    character(&app, entity).wall_running(),
+                    "detached in frame {frame}"
+                );
+                assert!(translation(&app, entity).x < 0.65);
+            }
+        }
+
+        let slow_distance = -translation(&app, slow).z;
+        let fast_distance = 20.0 - translation(&app, fast).z;
+        assert!(slow_distance > 0.3, "ran {slow_distance}");
+        assert!(
+            fast_distance > slow_distance + 0.5,
+            "ran {fast_distance} instead of {slow_distance}"
+        );
+    }
+
+    #[test]
+    fn character_without_step_up_is_blocked_by_a_step() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        // A step as tall as the default step height, starting at z = -1
+        spawn_box(
+            &mut app,
+            Vec3::new(20.0, STEP_HEIGHT, 10.0),
+            Transform::from_xyz(0.0, STEP_HEIGHT / 2.0, -6.0),
+        );
+
+        let stepping = spawn_character(&mut app, Vec3::new(-3.0, 0.86, 0.0), Character::default());
+        let blocked = spawn_character(
+            &mut app,
+            Vec3::new(3.0, 0.86, 0.0),
+            Character {
+                enable_step_up: false,
+                ..Default::default()
+            },
+        );
+        run(&mut app, 8);
+
+        press(&mut app, &[KeyCode::KeyW]);
+        run(&mut app, 48);
+
+        let stepped = translation(&app, stepping);
+        assert!(stepped.y > 0.86 + STEP_HEIGHT - 0.05 && stepped.z < -1.0);
+
+        // Still on the floor in front of the step
+        let stopped = translation(&app, blocked);
+        assert!(character(&app, blocked).grounded());
+        assert!(stopped.y < 0.9 && stopped.z > -0.7, "moved to {stopped}");
+    }
+
+    #[test]
+    fn speed_buff_blends_the_speed_over_the_duration() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        let buffed = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+        let speed = |app: &App| character(app, buffed).horizontal_velocity().length();
+
+        press(&mut app, &[KeyCode::KeyW]);
+        run(&mut app, 16);
+        assert!((speed(&app) - MOVEMENT_SPEED).abs() < 0.1);
+
+        app.world_mut()
+            .entity_mut(buffed)
+            .insert(MovementConfigBlend {
+                from: MovementConfig::default(),
+                to: MovementConfig {
+                    movement_speed: MOVEMENT_SPEED * 2.0,
+                    ..Default::default()
+                },
+                duration: 0.5,
+                elapsed: 0.0,
+            });
+
+        // A quarter of the way in the speed is a quarter of the way up, not at the buffed speed yet
+        run(&mut app, 8);
+        let quarter_speed = speed(&app);
+        assert!(
+            (quarter_speed - MOVEMENT_SPEED * 1.25).abs() < 0.5,
+            "at {quarter_speed} a quarter in"
+        );
+
+        run(&mut app, 32);
+        assert!((speed(&app) - MOVEMENT_SPEED * 2.0).abs() < 0.1);
+        assert!(app.world().get::<MovementConfigBlend>(buffed).is_none());
+    }
+
+    #[test]
+    fn character_pushing_into_a_wall_is_not_moving() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        // A wall facing +z at z = -1
+        spawn_box(
+            &mut app,
+            Vec3::new(20.0, 4.0, 1.0),
+            Transform::from_xyz(0.0, 2.0, -1.5),
+        );
+        let pushing = spawn_character(
+            &mut app,
+            Vec3::Y * 0.86,
+            (Character::default(), Crouch::default()),
+        );
+        run(&mut app, 8);
+
+        press(&mut app, &[KeyCode::KeyW]);
+        run(&mut app, 2);
+        assert!(character(&app, pushing).is_moving());
+
+        run(&mut app, 30);
+        assert!(!character(&app, pushing).is_moving());
+
+        // Crouching moves the center down, but not the character
+        press(&mut app, &[KeyCode::ControlLeft]);
+        app.update();
+        assert!(app.world().get::<Crouch>(pushing).unwrap().is_crouched());
+        assert!(!character(&app, pushing).is_moving());
+    }
+
+    #[test]
+    fn character_stays_grounded_on_a_slope_at_the_walkable_angle() {
+        let mut app = character_app();
+        let rotation = Quat::from_rotation_x(-WALKABLE_ANGLE);
+        let normal = rotation * Vec3::Y;
+        let slope = spawn_box(
+            &mut app,
+            Vec3::new(8.0, 0.2, 40.0),
+            Transform::from_translation(-normal * 0.1).with_rotation(rotation),
+        );
+        run(&mut app, 2);
+
+        // Standing on the slope, it's too steep to land on but not to stay on
+        let mut standing = Character::default();
+        standing.set_grounded(Some(Ground {
+            entity: slope,
+            normal: Dir3::new(normal).unwrap(),
+            point: Vec3::ZERO,
+        }));
+        let sliding = app
+            .world_mut()
+            .spawn((
+                standing,
+                Transform::from_translation(normal * 0.36 + Vec3::Y * 0.5),
+            ))
+            .id();
+
+        // Slides down without losing the ground in between
+        for frame in 0..32 {
+            app.update();
+            assert!(
+                character(&app, sliding).grounded(),
+                "lost the ground in frame {frame}"
+            );
+        }
+    }
+
+    #[test]
+    fn one_way_platform_is_passed_from_below_and_landed_on_from_above() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        // A thin platform 2 above the floor
+        let platform = app
+            .world_mut()
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(8.0, 0.2, 8.0),
+                Transform::from_xyz(0.0, 2.1, 0.0),
+                OneWayPlatform,
+            ))
+            .id();
+        let jumping = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+        run(&mut app, 8);
+
+        // Jump high enough to clear the platform
+        app.world_mut()
+            .get_mut::<Character>(jumping)
+            .unwrap()
+            .jump(10.0);
+
+        let mut highest = 0.0_f32;
+        for _ in 0..64 {
+            app.update();
+            highest = highest.max(translation(&app, jumping).y);
+        }
+
+        // Went through the platform on the way up and stands on top of it after falling back down
+        assert!(highest > 2.2 + 0.85, "only got up to {highest}");
+        assert_eq!(
+            character(&app, jumping).ground.map(|ground| ground.entity),
+            Some(platform)
+        );
+        assert!(translation(&app, jumping).y > 2.2 + 0.8);
+    }
+
+    #[test]
+    fn dashing_down_a_slope_stays_on_the_slope() {
+        let mut app = character_app();
+        // A slope going down towards +z
+        let rotation = Quat::from_rotation_x(20.0_f32.to_radians());
+        let normal = rotation * Vec3::Y;
+        spawn_box(
+            &mut app,
+            Vec3::new(8.0, 0.2, 40.0),
+            Transform::from_translation(-normal * 0.1).with_rotation(rotation),
+        );
+        run(&mut app, 2);
+        let dashing = spawn_character(
+            &mut app,
+            normal * 0.36 + Vec3::Y * 0.5,
+            Character::default(),
+        );
+        run(&mut app, 8);
+
+        let mut dashing_character = app.world_mut().get_mut::<Character>(dashing).unwrap();
+        dashing_character.dash(Dir3::Z, DASH_SPEED);
+
+        // Follows the slope instead of flying off of it
+        let velocity = dashing_character.velocity;
+        assert!(dashing_character.grounded());
+        assert!(velocity.dot(normal).abs() < 1e-4);
+        assert!(velocity.z > 0.0 && velocity.y < 0.0);
+
+        for frame in 0..8 {
+            app.update();
+            assert!(
+                character(&app, dashing).grounded(),
+                "lost the slope in frame {frame}"
+            );
+        }
+        assert!(translation(&app, dashing).z > 1.0);
+    }
+
+    #[test]
+    fn jumping_into_a_ceiling_keeps_the_horizontal_speed() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        // The bottom of the ceiling is 0.29 above the head of the standing capsule
+        spawn_box(
+            &mut app,
+            Vec3::new(40.0, 0.2, 40.0),
+            Transform::from_xyz(0.0, 2.1, 0.0),
+        );
+        let jumping = spawn_character(&mut app, Vec3::new(-10.0, 0.86, 0.0), Character::default());
+        press(&mut app, &[KeyCode::KeyD]);
+        run(&mut app, 16);
+        let speed = character(&app, jumping).velocity().x;
+        assert!((speed - MOVEMENT_SPEED).abs() < 0.1, "running at {speed}");
+
+        press(&mut app, &[KeyCode::Space]);
+        app.update();
+        assert!(character(&app, jumping).velocity().y > 0.0);
+        run(&mut app, 8);
+
+        // Bumping the head stopped the ascent, but not the run
+        let velocity = character(&app, jumping).velocity();
+        assert!(velocity.y <= 0.0, "moving at {velocity}");
+        assert!((velocity.x - speed).abs() < 0.1, "moving at {velocity}");
+        assert!(translation(&app, jumping).y + 0.85 < 2.0);
+    }
+
+    #[test]
+    fn walking_up_a_ramp_slides_along_a_sloped_ceiling() {
+        let mut app = character_app();
+        let angle = 20.0_f32.to_radians();
+        let rotation = Quat::from_rotation_z(angle);
+        let normal = rotation * Vec3::Y;
+        let uphill = rotation * Vec3::X;
+        // The ceiling is parallel to the ramp, `0.1` above the top of the capsule standing on it
+        let ceiling = 0.7 + angle.cos() + 0.1;
+        for offset in [-0.1, ceiling + 0.1] {
+            spawn_box(
+                &mut app,
+                Vec3::new(20.0, 0.2, 8.0),
+                Transform::from_translation(normal * offset).with_rotation(rotation),
+            );
+        }
+        run(&mut app, 2);
+        let head =
+            |app: &App, entity| translation(app, entity).dot(normal) + angle.cos() / 2.0 + 0.35;
+
+        // The bottom hemisphere is just above the ramp
+        let walking = spawn_character(
+            &mut app,
+            normal * 0.36 + Vec3::Y * 0.5,
+            Character::default(),
+        );
+        run(&mut app, 8);
+        assert!(character(&app, walking).grounded());
+
+        // Bump the head into the ceiling, it redirects the motion along the ramp instead of lifting off of it
+        let start = translation(&app, walking);
+        app.world_mut()
+            .get_mut::<Character>(walking)
+            .unwrap()
+            .set_velocity(uphill * 7.0 + normal * 10.0);
+        press(&mut app, &[KeyCode::KeyD]);
+        app.update();
+        assert!(character(&app, walking).grounded());
+        assert!(character(&app, walking).velocity().dot(normal) < 1e-3);
+
+        for frame in 0..32 {
+            app.update();
+            assert!(
+                character(&app, walking).grounded(),
+                "lost the ramp in frame {frame}"
+            );
+            assert!(head(&app, walking) < ceiling + 1e-3);
+        }
+        assert!((translation(&app, walking) - start).dot(uphill) > 2.0);
+    }
+
+    #[test]
+    fn surface_walkability_lets_the_character_stand_on_a_steep_slope() {
+        let mut app = character_app();
+        // Two slopes too steep to stand on by default, one of them grippy enough to stand on
+        let rotation = Quat::from_rotation_x(-55.0_f32.to_radians());
+        let normal = rotation * Vec3::Y;
+        let [grippy, regular] = [-5.0, 5.0].map(|x| {
+            spawn_box(
+                &mut app,
+                Vec3::new(8.0, 0.2, 40.0),
+                Transform::from_translation(Vec3::X * x - normal * 0.1).with_rotation(rotation),
+            )
+        });
+        app.world_mut()
+            .entity_mut(grippy)
+            .insert(SurfaceWalkability(70.0_f32.to_radians()));
+        run(&mut app, 2);
+
+        // Dropped just above the slopes
+        let [on_grippy, on_regular] = [-5.0, 5.0].map(|x| {
+            app.world_mut()
+                .spawn((
+                    Character::default(),
+                    Transform::from_translation(Vec3::X * x + normal * 0.4 + Vec3::Y * 0.5),
+                ))
+                .id()
+        });
+        run(&mut app, 16);
+
+        assert_eq!(
+            character(&app, on_grippy)
+                .ground
+                .map(|ground| ground.entity),
+            Some(grippy)
+        );
+        assert!(!character(&app, on_regular).grounded());
+    }
+
+    #[test]
+    fn aligning_to_a_slope_only_tilts_the_visual() {
+        let mut app = character_app();
+        // A slope going up towards -z, gentle enough to walk up without sliding back
+        let slope_angle = 20.0_f32.to_radians();
+        let rotation = Quat::from_rotation_x(slope_angle);
+        let normal = rotation * Vec3::Y;
+        spawn_box(
+            &mut app,
+            Vec3::new(8.0, 0.2, 40.0),
+            Transform::from_translation(-normal * 0.1).with_rotation(rotation),
+        );
+        run(&mut app, 2);
+
+        let start = normal * 0.36 + Vec3::Y * 0.5;
+        let aligned = spawn_character(&mut app, start + Vec3::X * 2.0, Character::default());
+        let visual = app
+            .world_mut()
+            .spawn((
+                AlignToSlope::default(),
+                Transform::default(),
+                ChildOf(aligned),
+            ))
+            .id();
+        let upright = spawn_character(&mut app, start - Vec3::X * 2.0, Character::default());
+
+        press(&mut app, &[KeyCode::KeyW]);
+        run(&mut app, 32);
+
+        // The visual leans into the slope
+        let tilt = app.world().get::<Transform>(visual).unwrap().rotation;
+        assert!(tilt.angle_between(Quat::IDENTITY) > slope_angle * 0.9);
+
+        // While the character itself moves exactly like the one without it
+        let (aligned_character, upright_character) =
+            (character(&app, aligned), character(&app, upright));
+        assert!(aligned_character.grounded() && upright_character.grounded());
+        assert!(
+            aligned_character
+                .velocity
+                .abs_diff_eq(upright_character.velocity, 1e-4)
+        );
+        assert!(
+            (translation(&app, aligned) - Vec3::X * 4.0)
+                .abs_diff_eq(translation(&app, upright), 1e-4)
+        );
+        assert_eq!(
+            app.world().get::<Transform>(aligned).unwrap().rotation,
+            Quat::IDENTITY
+        );
+    }
+
+    #[test]
+    fn phasing_into_a_wall_ends_outside_of_it() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        // A wall facing +z at z = -1
+        spawn_box(
+            &mut app,
+            Vec3::new(20.0, 4.0, 2.0),
+            Transform::from_xyz(0.0, 2.0, -2.0),
+        );
+        let phasing = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+        run(&mut app, 8);
+
+        // Walk into the wall for 8 updates
+        let mut character_mut = app.world_mut().get_mut::<Character>(phasing).unwrap();
+        character_mut.set_velocity(Vec3::NEG_Z * MOVEMENT_SPEED);
+        character_mut.phase(8.0 / 64.0);
+        press(&mut app, &[KeyCode::KeyW]);
+
+        run(&mut app, 7);
+        assert!(character(&app, phasing).phasing());
+        assert!(translation(&app, phasing).z < -0.7, "didn't enter the wall");
+
+        // Pushed back out the way it came in when the phase ends
+        app.update();
+        let translation = translation(&app, phasing);
+        assert!(!character(&app, phasing).phasing());
+        assert!(
+            translation.z > -0.66,
+            "ended inside the wall at {translation}"
+        );
+    }
+
+    #[test]
+    fn swimming_character_ascends_with_the_vertical_input() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        let swimming = spawn_character(
+            &mut app,
+            Vec3::Y * 0.86,
+            (
+                Character {
+                    movement_mode: MovementMode::Swimming,
+                    ..Default::default()
+                },
+                Actions::<FlyCameraContext>::default(),
+            ),
+        );
+        run(&mut app, 8);
+        let start = translation(&app, swimming);
+
+        // The friction slows swimming down like drag, so it's slower than walking
+        press(&mut app, &[KeyCode::KeyE]);
+        run(&mut app, 32);
+
+        assert!(character(&app, swimming).vertical_velocity().y > 0.0);
+        assert!(translation(&app, swimming).y > start.y + 0.2);
+    }
+
+    #[test]
+    fn changing_the_gravity_affects_every_character() {
+        let mut app = character_app();
+        let characters = [-5.0, 5.0]
+            .map(|x| spawn_character(&mut app, Vec3::new(x, 10.0, 0.0), Character::default()));
+
+        app.insert_resource(CharacterGravity(Vec3::NEG_Y * GRAVITY / 4.0));
+        run(&mut app, 32);
+
+        // Falling for half a second at a quarter of the gravity, it would be 10 m/s with the default gravity
+        for entity in characters {
+            let velocity = character(&app, entity).velocity().y;
+            assert!((-4.0..-2.0).contains(&velocity), "falling at {velocity}");
+        }
+
+        // A sideways gravity turns every character
+        app.insert_resource(CharacterGravity(Vec3::NEG_X * GRAVITY));
+        app.update();
+        for entity in characters {
+            assert_eq!(character(&app, entity).up(), Dir3::X);
+        }
+    }
+
+    #[test]
+    fn running_up_stairs_does_not_dip_between_steps() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        // 10 steps of 0.2 by 0.4 going up towards -z, starting at z = -1
+        for step in 1..=10 {
+            let height = step as f32 * 0.2;
+            spawn_box(
+                &mut app,
+                Vec3::new(4.0, height, 0.4),
+                Transform::from_xyz(0.0, height / 2.0, -0.8 - step as f32 * 0.4),
+            );
+        }
+        let running = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+        run(&mut app, 8);
+
+        press(&mut app, &[KeyCode::KeyW]);
+        let mut height = translation(&app, running).y;
+
+        // Only ever moves up until the top of the stairs
+        for _ in 0..64 {
+            if translation(&app, running).z < -4.8 {
+                break;
+            }
+            app.update();
+
+            let new_height = translation(&app, running).y;
+            assert!(character(&app, running).grounded());
+            assert!(
+                new_height > height - 2e-3,
+                "dipped from {height} to {new_height}"
+            );
+            height = new_height;
+        }
+        assert!(height > 0.86 + 1.9);
+    }
+
+    #[test]
+    fn walking_off_a_ledge_does_not_snap_down_early() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        // A ledge 0.2 above the floor, low enough to snap down from, ending at z = -2
+        spawn_box(
+            &mut app,
+            Vec3::new(20.0, 0.2, 12.0),
+            Transform::from_xyz(0.0, 0.1, 4.0),
+        );
+        let walking = spawn_character(&mut app, Vec3::Y * 1.06, Character::default());
+        run(&mut app, 8);
+        let ledge_height = translation(&app, walking).y;
+
+        press(&mut app, &[KeyCode::KeyW]);
+        for _ in 0..64 {
+            if translation(&app, walking).z < -4.0 {
+                break;
+            }
+            app.update();
+
+            // Stays on top of the ledge as long as it's center is above it
+            let after = translation(&app, walking);
+            if after.z > -2.0 {
+                assert!(
+                    after.y > ledge_height - 1e-3,
+                    "pulled down to {after} before the edge"
+                );
+            }
+        }
 
-                if let Some(ground) =
-                    Ground::new_if_walkable(hit.entity, hit.normal1, character.up, WALKABLE_ANGLE)
-                {
-                    new_ground = Some(ground);
+        // Ends up on the floor below
+        assert!(translation(&app, walking).y < ledge_height - 0.15);
+    }
 
-                    // If the ground is walkable, project motion on ground plane
-                    move_accel = project_motion_on_ground(move_accel, hit.normal1, character.up);
-                } else if let Some(step_result) = try_step_up_on_hit(
-                    collider,
-                    transform.translation,
-                    transform.rotation,
-                    character.up,
-                    hit.normal1,
-                    direction,
-                    max_distance - safe_distance,
-                    character.config.epsilon,
-                    &spatial_query,
-                    &filter.0,
-                    time.delta_secs(),
-                ) {
-                    new_ground = Some(step_result.ground);
+    #[test]
+    fn landing_magnetism_lands_on_a_platform_that_would_be_overshot() {
+        let mut app = character_app();
+        // Short floating platforms ending at z = -1.3, one per character
+        for x in [-3.0, 3.0] {
+            spawn_box(
+                &mut app,
+                Vec3::new(2.0, 0.2, 1.3),
+                Transform::from_xyz(x, -0.1, -0.65),
+            );
+        }
+        let magnetized = spawn_character(
+            &mut app,
+            Vec3::new(-3.0, 1.36, 0.0),
+            (Character::default(), LandingMagnetism::default()),
+        );
+        let regular = spawn_character(&mut app, Vec3::new(3.0, 1.36, 0.0), Character::default());
 
-                    // Step up
-                    transform.translation = step_result.translation;
-                } else {
-                    // If the ground is not walkable, project motion on wall plane
-                    move_accel = project_motion_on_wall(move_accel, hit.normal1, character.up);
-                }
+        // Run over the platforms while starting to fall 0.5 above them
+        for entity in [magnetized, regular] {
+            let mut character = app.world_mut().get_mut::<Character>(entity).unwrap();
+            character.set_velocity(Vec3::NEG_Z * MOVEMENT_SPEED);
+        }
+        press(&mut app, &[KeyCode::KeyW]);
+
+        let mut landed = [false; 2];
+        for _ in 0..24 {
+            app.update();
+
+            for (landed, entity) in landed.iter_mut().zip([magnetized, regular]) {
+                *landed |= character(&app, entity).grounded();
             }
         }
 
-        character.velocity += move_accel;
+        // Pulled down before overshooting the edge, without the magnetism it falls past it
+        assert_eq!(landed, [true, false]);
+        assert!(translation(&app, regular).y < 0.5);
+    }
 
-        let move_result = move_and_slide(
-            &spatial_query,
-            &collider,
-            transform.translation,
-            character.velocity,
-            transform.rotation,
-            character.config,
-            &filter.0,
-            time.delta_secs(),
-            |hit| {
-                if let Some(ground) = Ground::new_if_walkable(
-                    hit.hit_data.entity,
-                    hit.hit_data.normal1,
-                    character.up,
-                    WALKABLE_ANGLE,
-                ) {
-                    new_ground = Some(ground);
+    #[test]
+    fn character_without_input_comes_to_a_complete_stop() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        let stopping = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+        run(&mut app, 8);
+
+        app.world_mut()
+            .get_mut::<Character>(stopping)
+            .unwrap()
+            .set_velocity(Vec3::X * MOVEMENT_SPEED);
+        run(&mut app, 32);
+
+        assert_eq!(character(&app, stopping).velocity(), Vec3::ZERO);
+        assert!(!character(&app, stopping).is_moving());
+    }
 
-                    // Avoid sliding down slopes when just landing
-                    if !character.grounded() {
-                        *hit.velocity = project_motion_on_ground(
-                            *hit.velocity,
-                            hit.hit_data.normal1,
-                            character.up,
-                        );
+    #[test]
+    fn frozen_character_falls_but_ignores_the_input() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        let frozen = spawn_character(&mut app, Vec3::Y * 3.0, (Character::default(), InputFrozen));
+
+        press(&mut app, &[KeyCode::KeyW, KeyCode::Space]);
+        run(&mut app, 48);
+
+        // Gravity still pulls it onto the floor, but it doesn't walk or jump
+        let landed = translation(&app, frozen);
+        assert!(character(&app, frozen).grounded());
+        assert!(
+            landed.y < 0.9 && landed.xz().length() < 1e-3,
+            "moved to {landed}"
+        );
 
-                        character.velocity = project_motion_on_ground(
-                            character.velocity,
-                            hit.hit_data.normal1,
-                            character.up,
-                        );
-                    }
+        run(&mut app, 16);
+        assert!(translation(&app, frozen).abs_diff_eq(landed, 1e-3));
+    }
 
-                    return true;
-                }
+    #[test]
+    fn forcing_airborne_lasts_until_the_character_lands() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        let launched = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+        run(&mut app, 8);
+        assert!(character(&app, launched).grounded());
+
+        // A small launch, the ground check would snap it back down without forcing it airborne
+        let mut launched_character = app.world_mut().get_mut::<Character>(launched).unwrap();
+        launched_character.set_grounded(None);
+        launched_character.set_velocity(Vec3::Y * 2.0);
+
+        for frame in 0..4 {
+            app.update();
+            assert!(
+                !character(&app, launched).grounded(),
+                "grounded in frame {frame}"
+            );
+        }
+        assert!(translation(&app, launched).y > 0.9);
 
-                let grounded = character.grounded() || new_ground.is_some();
+        // The automatic ground check takes over again once it lands
+        run(&mut app, 32);
+        assert!(character(&app, launched).grounded());
+    }
 
-                // In order to try step up we need to be grounded and hitting a "wall".
-                if grounded {
-                    if let Some(step_result) = try_step_up_on_hit(
-                        collider,
-                        *hit.translation,
-                        transform.rotation,
-                        character.up,
-                        hit.hit_data.normal1,
-                        hit.direction,
-                        hit.remaining_motion,
-                        character.config.epsilon,
-                        &spatial_query,
-                        &filter.0,
-                        time.delta_secs(),
-                    ) {
-                        new_ground = Some(step_result.ground);
+    #[test]
+    fn flying_camera_keeps_the_movement_input_from_the_character() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        let walking = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+        let camera = app
+            .world_mut()
+            .spawn((Targeting(walking), FlyingCamera))
+            .id();
+        run(&mut app, 8);
+        let start = translation(&app, walking);
+
+        // The camera flies, the character stays in place
+        press(&mut app, &[KeyCode::KeyW]);
+        run(&mut app, 16);
+        assert!(translation(&app, walking).distance(start) < 1e-3);
+        assert!(character(&app, walking).grounded());
+
+        // Back to walking with the same input
+        app.world_mut().entity_mut(camera).remove::<FlyingCamera>();
+        run(&mut app, 8);
+        assert!(translation(&app, walking).z < start.z - 0.3);
+    }
 
-                        // Subtract the stepped distance from remaining time to avoid moving further
-                        *hit.remaining_time =
-                            (*hit.remaining_time - step_result.move_time).max(0.0);
+    #[test]
+    fn dynamic_body_response_decides_how_the_character_hits_a_box() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+
+        // One lane per response, each with a dynamic box starting at z = -1.5 in front of the character.
+        // The box of the character stepping on it is low enough to step onto.
+        let lanes = [
+            (DynamicBodyResponse::Block, 1.0, -9.0),
+            (DynamicBodyResponse::Push, 1.0, -3.0),
+            (DynamicBodyResponse::Ignore, 1.0, 3.0),
+            (DynamicBodyResponse::StepOn, STEP_HEIGHT * 0.8, 9.0),
+        ]
+        .map(|(response, height, x)| {
+            let body = app
+                .world_mut()
+                .spawn((
+                    RigidBody::Dynamic,
+                    Collider::cuboid(2.0, height, 2.0),
+                    Transform::from_xyz(x, height / 2.0, -2.5),
+                ))
+                .id();
+            let character = spawn_character(
+                &mut app,
+                Vec3::new(x, 0.86, 0.0),
+                Character {
+                    dynamic_body_response: response,
+                    ..Default::default()
+                },
+            );
 
-                        // We need to override the translation here because the we stepped up
-                        *hit.translation = step_result.translation;
+            (body, character)
+        });
 
-                        // Successfully stepped, don't slide this iteration
-                        return false;
-                    }
-                }
+        press(&mut app, &[KeyCode::KeyW]);
+        run(&mut app, 32);
 
-                // Slide vleocity along walls
-                match grounded {
-                    // Avoid sliding up walls when grounded
-                    true => {
-                        character.velocity = project_motion_on_wall(
-                            character.velocity,
-                            hit.hit_data.normal1,
-                            character.up,
-                        );
+        let [block, push, ignore, step_on] = lanes;
 
-                        *hit.velocity = project_motion_on_wall(
-                            *hit.velocity,
-                            hit.hit_data.normal1,
-                            character.up,
-                        )
-                    }
-                    false => {
-                        character.velocity = character.velocity.reject_from(hit.hit_data.normal1)
-                    }
-                };
+        // Stopped in front of the box without moving it
+        let blocked = translation(&app, block.1);
+        assert!(blocked.z > -1.2 && blocked.y < 0.9, "moved to {blocked}");
+        assert!(translation(&app, block.0).z > -2.6);
 
-                true
-            },
+        // Pushed the box along while walking into it
+        assert!(translation(&app, push.0).z < -3.0);
+        assert!(translation(&app, push.1).z < -1.5);
+
+        // Walked straight through the box
+        let ignored = translation(&app, ignore.1);
+        assert!(ignored.z < -3.5 && ignored.y < 0.9, "moved to {ignored}");
+
+        // Stepped up and stands on the box
+        let stepped = translation(&app, step_on.1);
+        assert!(stepped.y > 0.86 + STEP_HEIGHT * 0.7, "moved to {stepped}");
+        assert_eq!(
+            character(&app, step_on.1)
+                .ground
+                .map(|ground| ground.entity),
+            Some(step_on.0)
         );
+    }
 
-        transform.translation = move_result.new_translation;
+    #[test]
+    fn standing_in_a_corner_touches_the_floor_and_both_walls() {
+        let mut app = character_app();
+        let floor = spawn_floor(&mut app);
+        // Walls facing +z at z = -1 and facing -x at x = 1
+        let back_wall = spawn_box(
+            &mut app,
+            Vec3::new(20.0, 4.0, 1.0),
+            Transform::from_xyz(0.0, 2.0, -1.5),
+        );
+        let side_wall = spawn_box(
+            &mut app,
+            Vec3::new(1.0, 4.0, 20.0),
+            Transform::from_xyz(1.5, 2.0, 0.0),
+        );
+        let cornered = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+
+        // Walk diagonally into the corner
+        press(&mut app, &[KeyCode::KeyW, KeyCode::KeyD]);
+        run(&mut app, 32);
+
+        let mut contacts = character(&app, cornered)
+            .contacts()
+            .iter()
+            .map(|contact| (contact.entity, contact.kind))
+            .collect::<Vec<_>>();
+        contacts.sort_by_key(|&(entity, _)| entity);
+
+        let mut expected = vec![
+            (floor, SurfaceKind::Ground),
+            (back_wall, SurfaceKind::Wall),
+            (side_wall, SurfaceKind::Wall),
+        ];
+        expected.sort_by_key(|&(entity, _)| entity);
+
+        assert_eq!(contacts, expected);
+    }
 
-        // Check if the previous ground is still there and snap to it
-        if character.grounded() {
-            if let Some((safe_distance, ground)) = ground_check(
-                &collider,
-                character.config,
-                transform.translation,
-                character.up,
-                transform.rotation,
-                &spatial_query,
-                &filter.0,
-                GROUND_CHECK_DISTANCE,
-                WALKABLE_ANGLE,
-            ) {
-                transform.translation -= character.up * safe_distance;
-                new_ground = Some(ground);
+    #[test]
+    fn character_input_matches_the_actions_every_frame() {
+        let mut app = character_app();
+        spawn_floor(&mut app);
+        let player = spawn_character(&mut app, Vec3::Y * 0.86, Character::default());
+
+        let diagonal = Vec3::new(1.0, 0.0, -1.0).normalize();
+        let frames = [
+            (vec![], Vec3::ZERO, 0.0, false, false),
+            (
+                vec![KeyCode::KeyW],
+                Vec3::NEG_Z,
+                MOVEMENT_SPEED,
+                false,
+                false,
+            ),
+            (
+                vec![KeyCode::KeyW, KeyCode::KeyD],
+                diagonal,
+                MOVEMENT_SPEED,
+                false,
+                false,
+            ),
+            (vec![KeyCode::Space], Vec3::ZERO, 0.0, true, false),
+            // Jumping only fires on the press
+            (vec![KeyCode::Space], Vec3::ZERO, 0.0, false, false),
+            (vec![KeyCode::ControlLeft], Vec3::ZERO, 0.0, false, true),
+            (vec![KeyCode::ControlLeft], Vec3::ZERO, 0.0, false, true),
+            (vec![], Vec3::ZERO, 0.0, false, false),
+        ];
+
+        for (frame, (keys, wish_dir, wish_speed, jump, crouch)) in frames.into_iter().enumerate() {
+            app.world_mut()
+                .resource_mut::<ButtonInput<KeyCode>>()
+                .release_all();
+            press(&mut app, &keys);
+            app.update();
+
+            let input = app.world().get::<CharacterInput>(player).unwrap();
+            assert!(
+                input.wish_dir.abs_diff_eq(wish_dir, 1e-4)
+                    && (input.wish_speed - wish_speed).abs() < 1e-4
+                    && input.jump == jump
+                    && input.crouch == crouch,
+                "{input:?} in frame {frame}"
+            );
+        }
+    }
+
+    /// Moves along `x` at a constant speed, like an animated platform.
+    #[derive(Component)]
+    struct SlidingPlatform(f32);
+
+    fn slide_platforms(mut platforms: Query<(&mut Transform, &SlidingPlatform)>, time: Res<Time>) {
+        for (mut transform, platform) in &mut platforms {
+            transform.translation.x += platform.0 * time.delta_secs();
+        }
+    }
+
+    /// Returns an app with a [`Character`] standing on a [`SlidingPlatform`] that starts moving at `speed`,
+    /// along with the character and platform entities.
+    fn sliding_platform_app(speed: f32) -> (App, Entity, Entity) {
+        let mut app = character_app();
+        // Animated every frame, the character follows it in the next fixed update
+        app.add_systems(Update, slide_platforms);
+
+        let platform = app
+            .world_mut()
+            .spawn((
+                RigidBody::Kinematic,
+                Collider::cuboid(100.0, 0.5, 10.0),
+                Transform::default(),
+                SlidingPlatform(0.0),
+            ))
+            .id();
+        let character = app
+            .world_mut()
+            .spawn((Character::default(), Transform::from_xyz(0.0, 1.2, 0.0)))
+            .id();
+
+        // Land on the platform before it starts moving
+        run(&mut app, 32);
+        assert!(app.world().get::<Character>(character).unwrap().grounded());
+
+        app.world_mut()
+            .get_mut::<SlidingPlatform>(platform)
+            .unwrap()
+            .0 = speed;
+
+        (app, character, platform)
+    }
+
+    #[test]
+    fn character_does_not_drift_on_sliding_platform() {
+        let (mut app, character, platform) = sliding_platform_app(4.0);
+        let offset = |app: &App| {
+            let world = app.world();
+            world.get::<Transform>(character).unwrap().translation
+                - world.get::<Transform>(platform).unwrap().translation
+        };
+
+        app.update();
+        let start_offset = offset(&app);
+
+        for _ in 0..128 {
+            app.update();
+        }
+
+        let drift = offset(&app) - start_offset;
+        assert!(drift.length() < 1e-3, "drifted by {drift}");
+    }
+
+    #[test]
+    fn character_is_not_carried_by_too_fast_platform() {
+        let (mut app, character, _) = sliding_platform_app(MAX_PLATFORM_CARRY_SPEED * 2.0);
+
+        for _ in 0..16 {
+            app.update();
+        }
+
+        let translation = app.world().get::<Transform>(character).unwrap().translation;
+        assert!(translation.x.abs() < 1e-3, "carried to {translation}");
+    }
+
+    #[test]
+    fn landing_on_a_fast_platform_does_not_lurch() {
+        let speed = 15.0;
+        let mut app = character_app();
+        app.add_systems(Update, slide_platforms);
+
+        let platform = app
+            .world_mut()
+            .spawn((
+                RigidBody::Kinematic,
+                Collider::cuboid(100.0, 0.5, 10.0),
+                Transform::default(),
+                SlidingPlatform(speed),
+            ))
+            .id();
+        let falling = app
+            .world_mut()
+            .spawn((Character::default(), Transform::from_xyz(0.0, 2.0, 0.0)))
+            .id();
+
+        let offset = |app: &App| translation(app, falling).x - translation(app, platform).x;
+        let mut landed_offset = None;
+        let mut previous = translation(&app, falling).x;
+
+        for frame in 0..64 {
+            app.update();
+
+            // Never carried further than the platform moved in a single frame
+            let moved = translation(&app, falling).x - previous;
+            assert!(
+                moved < speed / 64.0 + 1e-3,
+                "lurched by {moved} in frame {frame}"
+            );
+            previous += moved;
+
+            if character(&app, falling).grounded() {
+                landed_offset.get_or_insert(offset(&app));
             }
         }
 
-        // let h = character
-        //     .velocity
-        //     .reject_from_normalized(*character.up)
-        //     .length();
-        // let v = character
-        //     .velocity
-        //     .project_onto_normalized(*character.up)
-        //     .length();
-        // let all = character.velocity.length();
-        // dbg!([h, v, all]);
+        // Carried along from the frame it landed on
+        let landed_offset = landed_offset.expect("never landed");
+        assert!((offset(&app) - landed_offset).abs() < 1e-3);
+    }
 
-        // Update the ground
-        character.ground = new_ground;
+    #[test]
+    fn leaving_a_fast_platform_inherits_a_capped_velocity() {
+        // Slow enough to be carried, but faster than the inherited speed limit
+        let (mut app, jumping, _) = sliding_platform_app(MAX_PLATFORM_CARRY_SPEED * 0.9);
+        run(&mut app, 4);
+
+        app.world_mut()
+            .get_mut::<Character>(jumping)
+            .unwrap()
+            .jump(JUMP_IMPULSE);
+        app.update();
+
+        let character = character(&app, jumping);
+        let inherited = character.horizontal_velocity().length();
+        assert!(!character.grounded());
+        assert!(
+            (inherited - MAX_INHERITED_PLATFORM_SPEED).abs() < 1e-3,
+            "inherited {inherited}"
+        );
     }
-}
 
-struct StepUpResult {
-    translation: Vec3,
-    move_time: f32,
-    ground: Ground,
-}
+    #[test]
+    fn leaving_a_platform_inherits_the_scaled_velocity() {
+        let (mut app, jumping, _) = sliding_platform_app(4.0);
+        app.world_mut()
+            .get_mut::<Character>(jumping)
+            .unwrap()
+            .platform_inherit_scale = 0.5;
+        run(&mut app, 4);
+
+        app.world_mut()
+            .get_mut::<Character>(jumping)
+            .unwrap()
+            .jump(JUMP_IMPULSE);
+        app.update();
+
+        let character = character(&app, jumping);
+        let inherited = character.horizontal_velocity();
+        assert!(!character.grounded());
+        assert!(
+            inherited.abs_diff_eq(Vec3::X * 2.0, 1e-3),
+            "inherited {inherited}"
+        );
+    }
 
-fn try_step_up_on_hit(
-    collider: &Collider,
-    translation: Vec3,
-    rotation: Quat,
-    up: Dir3,
-    hit_normal: Vec3,
-    direction: Dir3,
-    mut step_forward: f32,
-    epsilon: f32,
-    spatial_query: &SpatialQuery,
-    filter: &SpatialQueryFilter,
-    delta_time: f32,
-) -> Option<StepUpResult> {
-    let horizontal_normal = hit_normal.reject_from_normalized(*up).normalize_or_zero();
+    /// Moves up and down around `y = 0` with the `amplitude` and `frequency`, like an elevator gone wrong.
+    #[derive(Component)]
+    struct OscillatingPlatform {
+        amplitude: f32,
+        frequency: f32,
+        elapsed: f32,
+    }
 
-    // This is necessary for capsule colliders since the normal angle changes depending on
-    // how far out on a ledge the character is standing
-    let a = 1.0 - WALKABLE_ANGLE.cos();
-    let min_inward_distance = CHARACTER_RADIUS * a;
+    fn oscillate_platforms(
+        mut platforms: Query<(&mut Transform, &mut OscillatingPlatform)>,
+        time: Res<Time>,
+    ) {
+        for (mut transform, mut platform) in &mut platforms {
+            platform.elapsed += time.delta_secs();
+            transform.translation.y =
+                platform.amplitude * (TAU * platform.frequency * platform.elapsed).sin();
+        }
+    }
 
-    // Step into the hit normal alil bit, this helps with the capsule collider.
-    // Cylinders don't need this since they have a flat bottom.
-    let inward = min_inward_distance + epsilon * PI;
+    #[test]
+    fn character_keeps_up_with_a_fast_vertical_platform() {
+        let mut app = character_app();
+        app.add_systems(
+            FixedUpdate,
+            oscillate_platforms.before(CharacterSystems::Accelerate),
+        );
+        let platform = app
+            .world_mut()
+            .spawn((
+                RigidBody::Kinematic,
+                Collider::cuboid(8.0, 0.5, 8.0),
+                Transform::default(),
+                OscillatingPlatform {
+                    amplitude: 0.0,
+                    frequency: 2.0,
+                    elapsed: 0.0,
+                },
+            ))
+            .id();
+        let riding = spawn_character(&mut app, Vec3::Y * 1.11, Character::default());
+        run(&mut app, 8);
+        assert!(character(&app, riding).grounded());
+
+        // Up and down by half a meter twice per second
+        app.world_mut()
+            .get_mut::<OscillatingPlatform>(platform)
+            .unwrap()
+            .amplitude = 0.5;
+        let gap = |app: &App| translation(app, riding).y - translation(app, platform).y;
+        let start_gap = gap(&app);
+
+        for frame in 0..128 {
+            app.update();
+
+            let trailed = gap(&app) - start_gap;
+            assert!(
+                trailed.abs() < MoveAndSlideConfig::default().epsilon,
+                "trailed by {trailed} in frame {frame}"
+            );
+        }
+    }
 
-    // Step a lil bit less forward to account for stepping into the hit normal
-    step_forward = (step_forward - inward).max(0.0);
+    #[test]
+    fn walking_off_a_fast_rising_platform_does_not_launch_the_character() {
+        let mut app = character_app();
+        app.add_systems(
+            FixedUpdate,
+            oscillate_platforms.before(CharacterSystems::Accelerate),
+        );
+        // Platforms ending at z = -1, starting to rise at 40 m/s once they oscillate
+        let platforms = [-3.0, 3.0].map(|x| {
+            app.world_mut()
+                .spawn((
+                    RigidBody::Kinematic,
+                    Collider::cuboid(2.0, 0.5, 2.0),
+                    Transform::from_xyz(x, 0.0, 0.0),
+                    OscillatingPlatform {
+                        amplitude: 0.0,
+                        frequency: 1.0 / PI,
+                        elapsed: 0.0,
+                    },
+                ))
+                .id()
+        });
+        let capped = spawn_character(&mut app, Vec3::new(-3.0, 1.11, -0.8), Character::default());
+        let horizontal_only = spawn_character(
+            &mut app,
+            Vec3::new(3.0, 1.11, -0.8),
+            Character {
+                inherit_horizontal_platform_velocity_only: true,
+                ..Default::default()
+            },
+        );
+        run(&mut app, 8);
 
-    let step_motion = direction * step_forward - horizontal_normal * inward;
+        for platform in platforms {
+            app.world_mut()
+                .get_mut::<OscillatingPlatform>(platform)
+                .unwrap()
+                .amplitude = 20.0;
+        }
+        press(&mut app, &[KeyCode::KeyW]);
 
-    let Some((step_translation, hit)) = try_climb_step(
-        spatial_query,
-        &collider,
-        translation,
-        step_motion,
-        rotation,
-        up,
-        STEP_HEIGHT + GROUND_CHECK_DISTANCE,
-        epsilon,
-        &filter,
-    ) else {
-        // Can't stand here, slide instead
-        return None;
-    };
+        // The upward velocity right after walking off the edge
+        let mut launch_speeds = [None; 2];
+        for _ in 0..32 {
+            app.update();
 
-    let ground = Ground::new_if_walkable(
-        hit.entity,
-        hit.normal1,
-        up,
-        // Subtract a small amount from walkable angle to make sure we can't step
-        // on surfaces that are nearly excactly the walkable angle of the character
-        WALKABLE_ANGLE - 1e-4,
-    )?;
+            for (launch_speed, entity) in launch_speeds.iter_mut().zip([capped, horizontal_only]) {
+                let walking = character(&app, entity);
+                if !walking.grounded() && launch_speed.is_none() {
+                    *launch_speed = Some(walking.velocity.y);
+                }
+            }
+        }
 
-    if !is_walkable(hit.normal1, up, WALKABLE_ANGLE - 1e-4) {
-        return None;
+        let [Some(capped_speed), Some(horizontal_only_speed)] = launch_speeds else {
+            panic!("never walked off the platforms");
+        };
+        assert!(
+            capped_speed > 0.0 && capped_speed < MAX_INHERITED_PLATFORM_SPEED + 0.5,
+            "launched at {capped_speed}"
+        );
+        assert!(
+            horizontal_only_speed < 0.5,
+            "launched at {horizontal_only_speed}"
+        );
     }
 
-    // Subtract the stepped distance from remaining time to avoid moving further
-    let move_time = (step_forward + inward) * delta_time;
+    /// Turns around `y` at a constant angular speed, like a turntable.
+    #[derive(Component)]
+    struct SpinningPlatform(f32);
 
-    Some(StepUpResult {
-        translation: step_translation,
-        move_time,
-        ground,
-    })
-}
+    fn spin_platforms(mut platforms: Query<(&mut Transform, &SpinningPlatform)>, time: Res<Time>) {
+        for (mut transform, platform) in &mut platforms {
+            transform.rotate_y(platform.0 * time.delta_secs());
+        }
+    }
 
-/// This is a simple example inspired by Quake, users are expected to bring their own logic for acceleration.
-#[must_use]
-fn acceleration(
-    velocity: Vec3,
-    direction: impl TryInto<Dir3>,
-    max_acceleration: f32,
-    target_speed: f32,
-    delta: f32,
-) -> Vec3 {
-    let Ok(direction) = direction.try_into() else {
-        return Vec3::ZERO;
-    };
+    #[test]
+    fn turntable_carries_the_character_without_turning_it() {
+        let mut app = character_app();
+        app.add_systems(
+            FixedUpdate,
+            spin_platforms.before(CharacterSystems::Accelerate),
+        );
+        let turntable = app
+            .world_mut()
+            .spawn((
+                RigidBody::Kinematic,
+                Collider::cuboid(8.0, 0.5, 8.0),
+                Transform::from_xyz(0.0, -0.25, 0.0),
+                SpinningPlatform(0.0),
+            ))
+            .id();
+        let fixed = spawn_character(&mut app, Vec3::new(2.0, 0.86, 0.0), Character::default());
+        let turning = spawn_character(
+            &mut app,
+            Vec3::new(-2.0, 0.86, 0.0),
+            Character {
+                inherit_platform_rotation: true,
+                ..Default::default()
+            },
+        );
+        run(&mut app, 8);
+
+        // A full turn per second
+        app.world_mut()
+            .get_mut::<SpinningPlatform>(turntable)
+            .unwrap()
+            .0 = TAU;
+        let starts = [fixed, turning].map(|entity| translation(&app, entity));
+        run(&mut app, 16);
+
+        // Both are carried around the center
+        let platform_rotation = app.world().get::<Transform>(turntable).unwrap().rotation;
+        for (entity, start) in [fixed, turning].into_iter().zip(starts) {
+            let carried = translation(&app, entity);
+            assert!(
+                carried.abs_diff_eq(platform_rotation * start, 0.05),
+                "carried to {carried} instead of {}",
+                platform_rotation * start
+            );
+        }
 
-    // Current speed in the desired direction.
-    let current_speed = velocity.dot(*direction);
+        // Only the one inheriting the rotation turned with it
+        let rotation = |entity| app.world().get::<Transform>(entity).unwrap().rotation;
+        assert_eq!(rotation(fixed), Quat::IDENTITY);
+        assert!(rotation(turning).angle_between(platform_rotation) < 0.05);
+    }
 
-    // No acceleration is needed if current speed exceeds target.
-    if current_speed >= target_speed {
-        return Vec3::ZERO;
+    #[test]
+    fn parented_character_collides_with_the_world() {
+        let mut app = character_app();
+        app.add_systems(Update, slide_platforms);
+        spawn_floor(&mut app);
+        // A wall facing -x at x = 14
+        spawn_box(
+            &mut app,
+            Vec3::new(1.0, 4.0, 20.0),
+            Transform::from_xyz(14.5, 2.0, 0.0),
+        );
+        // A vehicle without a collider driving towards the wall
+        let vehicle = app
+            .world_mut()
+            .spawn((Transform::from_xyz(10.0, 0.0, 0.0), SlidingPlatform(2.0)))
+            .id();
+        let mounted = spawn_character(
+            &mut app,
+            Vec3::Y * 0.86,
+            (Character::default(), ChildOf(vehicle)),
+        );
+
+        press(&mut app, &[KeyCode::KeyD]);
+        run(&mut app, 64);
+
+        // Stopped by the wall in world space, while still following the vehicle
+        let global = app
+            .world()
+            .get::<GlobalTransform>(mounted)
+            .unwrap()
+            .translation();
+        assert!(
+            global.x > 13.5 && global.x < 14.0 - 0.35 + 0.05,
+            "moved to {global}"
+        );
+        assert!((global.y - 0.86).abs() < 0.05, "moved to {global}");
+        assert!(app.world().get::<ChildOf>(mounted).is_some());
     }
 
-    // Clamp to avoid acceleration past the target speed.
-    let accel_speed = f32::min(target_speed - current_speed, max_acceleration * delta);
+    /// Returns how many full updates every one of `count` characters got over `frames` frames.
+    fn full_updates(budget: &mut CharacterUpdateBudget, count: usize, frames: usize) -> Vec<usize> {
+        let mut updates = vec![0; count];
 
-    direction * accel_speed
-}
+        for _ in 0..frames {
+            for (index, updates) in updates.iter_mut().enumerate() {
+                if budget.is_full_update(index, count) {
+                    *updates += 1;
+                }
+            }
 
-/// Constant acceleration in the opposite direction of velocity.
-#[must_use]
-pub fn friction(velocity: Vec3, friction: f32, delta: f32) -> Vec3 {
-    let speed_sq = velocity.length_squared();
+            budget.advance(count);
+        }
 
-    if speed_sq < 1e-4 {
-        return Vec3::ZERO;
+        updates
     }
 
-    let factor = f32::exp(-friction / speed_sq.sqrt() * delta);
+    #[test]
+    fn update_budget_is_round_robin() {
+        let mut budget = CharacterUpdateBudget {
+            max_full_updates_per_frame: Some(10),
+            ..Default::default()
+        };
 
-    -velocity * (1.0 - factor)
+        // Every character is fully updated exactly once every 10 frames
+        assert!(
+            full_updates(&mut budget, 100, 10)
+                .iter()
+                .all(|&updates| updates == 1)
+        );
+        assert!(
+            full_updates(&mut budget, 100, 10)
+                .iter()
+                .all(|&updates| updates == 1)
+        );
+    }
+
+    #[test]
+    fn update_budget_serves_uneven_counts() {
+        let mut budget = CharacterUpdateBudget {
+            max_full_updates_per_frame: Some(3),
+            ..Default::default()
+        };
+
+        // 7 characters with 3 updates per frame are all served within 3 frames
+        assert!(
+            full_updates(&mut budget, 7, 3)
+                .iter()
+                .all(|&updates| updates >= 1)
+        );
+    }
+
+    #[test]
+    fn update_budget_without_limit_updates_everyone() {
+        let mut budget = CharacterUpdateBudget::default();
+
+        assert!(
+            full_updates(&mut budget, 5, 2)
+                .iter()
+                .all(|&updates| updates == 2)
+        );
+    }
 }
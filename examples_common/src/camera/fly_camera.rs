@@ -1,4 +1,9 @@
-use crate::input::{DefaultContext, Fly, FlyCameraContext, Move};
+use crate::{
+    ActiveMovementConsumer, CharacterUp,
+    input::{
+        DefaultContext, Fly, FlyCameraContext, FlySprint, Move, OrbitCameraContext, OrbitZoom,
+    },
+};
 use bevy::prelude::*;
 use bevy_enhanced_input::prelude::*;
 
@@ -7,22 +12,40 @@ use super::TargetedBy;
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         RunFixedMainLoop,
-        fly_input.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+        fly_input
+            .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop)
+            .run_if(in_state(ActiveMovementConsumer::FlyCamera)),
     );
 }
 
+/// Marks a camera that flies around freely, it consumes the movement input of the entity it's targeting.
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
 #[require(FlySpeed)]
-pub(super) struct FlyingCamera;
+pub struct FlyingCamera;
 
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
-pub(super) struct FlySpeed(pub f32);
+pub(super) struct FlySpeed {
+    pub speed: f32,
+    /// The fraction of the current speed changed per unit of scroll input.
+    pub scroll_factor: f32,
+    /// The range the scroll input can change the speed in.
+    pub min_speed: f32,
+    pub max_speed: f32,
+    /// Multiplies the speed while the sprint input is held.
+    pub sprint_multiplier: f32,
+}
 
 impl Default for FlySpeed {
     fn default() -> Self {
-        Self(10.0)
+        Self {
+            speed: 10.0,
+            scroll_factor: 0.1,
+            min_speed: 1.0,
+            max_speed: 200.0,
+            sprint_multiplier: 5.0,
+        }
     }
 }
 
@@ -30,24 +53,38 @@ fn fly_input(
     targets: Query<(
         &Actions<DefaultContext>,
         &Actions<FlyCameraContext>,
+        Option<&Actions<OrbitCameraContext>>,
         &TargetedBy,
+        Option<&CharacterUp>,
     )>,
-    mut cameras: Query<(&mut Transform, &FlySpeed), With<FlyingCamera>>,
+    mut cameras: Query<(&mut Transform, &mut FlySpeed), With<FlyingCamera>>,
     time: Res<Time>,
 ) {
-    for (default_actions, fly_actions, attachments) in &targets {
+    for (default_actions, fly_actions, orbit_actions, attachments, up) in &targets {
         let move_input = default_actions.value::<Move>().unwrap_or_default();
         let fly_input = fly_actions.value::<Fly>().unwrap_or_default();
-
-        if move_input == Vec2::ZERO && fly_input == 0.0 {
-            continue;
-        }
+        let sprint = fly_actions.value::<FlySprint>().unwrap_or_default();
+        let scroll_input = orbit_actions
+            .and_then(|actions| actions.value::<OrbitZoom>().ok())
+            .unwrap_or_default();
 
         let mut iter = cameras.iter_many_mut(attachments.iter());
-        while let Some((mut transform, speed)) = iter.fetch_next() {
+        while let Some((mut transform, mut speed)) = iter.fetch_next() {
+            // Scrolling changes the speed proportionally, like zooming the orbit camera
+            if scroll_input.y != 0.0 {
+                let speed_delta = scroll_input.y * speed.speed * speed.scroll_factor;
+                speed.speed = (speed.speed + speed_delta).clamp(speed.min_speed, speed.max_speed);
+            }
+
+            if move_input == Vec2::ZERO && fly_input == 0.0 {
+                continue;
+            }
+
+            let multiplier = if sprint { speed.sprint_multiplier } else { 1.0 };
+
             let mut direction = transform.rotation * Vec3::new(move_input.x, 0.0, -move_input.y);
-            direction.y += fly_input;
-            transform.translation += direction * speed.0 * time.delta_secs();
+            direction += up.map_or(Dir3::Y, |up| up.0) * fly_input;
+            transform.translation += direction * speed.speed * multiplier * time.delta_secs();
         }
     }
 }
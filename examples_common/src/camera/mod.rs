@@ -2,7 +2,7 @@ pub mod fly_camera;
 pub mod orbit_camera;
 
 use crate::{
-    Frozen,
+    ActiveMovementConsumer, CharacterUp,
     input::{DefaultContext, Look, ToggleFlyCam, ToggleViewPerspective},
 };
 use bevy::prelude::*;
@@ -18,7 +18,9 @@ impl Plugin for CameraPlugin {
         app.add_plugins((fly_camera::plugin, orbit_camera::plugin))
             .add_systems(
                 RunFixedMainLoop,
-                view_input.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                (view_input, face_camera_yaw)
+                    .chain()
+                    .in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
             )
             .add_systems(Update, update_origin)
             .add_observer(toggle_cam_perspective)
@@ -38,6 +40,18 @@ pub struct Targeting(pub Entity);
 #[relationship_target(relationship = Targeting)]
 pub struct TargetedBy(Entity);
 
+/// Marks a descendant of a targeted entity (e.g. a head bone) as the point the camera follows instead of the entity itself.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct CameraSocket;
+
+/// Rotates the targeted entity to face the yaw of a first person camera targeting it, e.g. so held items align with the view.
+///
+/// Only the yaw is applied so the entity stays upright relative to it's [`CharacterUp`].
+#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct FaceCameraYaw;
+
 /// The look sensitivity of a camera
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
@@ -61,6 +75,11 @@ impl ViewAngles {
     pub fn to_quat(&self) -> Quat {
         Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, self.roll)
     }
+
+    /// Returns the rotation of the view angles relative to the `up` direction instead of [`Vec3::Y`].
+    pub fn to_quat_with_up(&self, up: Dir3) -> Quat {
+        Quat::from_rotation_arc(Vec3::Y, *up) * self.to_quat()
+    }
 }
 
 /// The origin of an attached camera, corresponds to the translation of the [`AttachedTo`] entity + [`FollowOffset`]
@@ -70,11 +89,51 @@ impl ViewAngles {
 pub(crate) struct FollowOrigin(pub Vec3);
 
 /// The offset of an attached camera
-#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+#[derive(Component, Reflect, Debug, Clone, Copy)]
 #[reflect(Component)]
 pub struct FollowOffset {
+    /// The offset from the target, where [`Vec3::Y`] is the [`CharacterUp`] of the target.
     pub absolute: Vec3,
+    /// The offset rotated by the view angles of the camera.
     pub relative: Vec3,
+    /// The `absolute` offset smoothly moves towards this when set, e.g. to lower the camera while crouching.
+    pub target_absolute: Option<Vec3>,
+    /// How quickly the `absolute` offset catches up with the `target_absolute`, higher is stiffer.
+    pub lerp_speed: f32,
+}
+
+impl Default for FollowOffset {
+    fn default() -> Self {
+        Self {
+            absolute: Vec3::ZERO,
+            relative: Vec3::ZERO,
+            target_absolute: None,
+            lerp_speed: 10.0,
+        }
+    }
+}
+
+/// Smoothly moves the [`FollowOrigin`] towards the target instead of snapping to it.
+///
+/// The origin always snaps when it's first added so it doesn't smooth across the whole level.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct FollowSmoothing {
+    /// How quickly the origin catches up with the target, higher is stiffer.
+    pub speed: f32,
+    /// Snap to the target on the next update instead of smoothing, e.g. after teleporting.
+    ///
+    /// This is reset after snapping.
+    pub snap: bool,
+}
+
+impl Default for FollowSmoothing {
+    fn default() -> Self {
+        Self {
+            speed: 10.0,
+            snap: false,
+        }
+    }
 }
 
 fn toggle_cam_perspective(
@@ -96,6 +155,7 @@ fn toggle_cam_perspective(
 fn toggle_fly_cam(
     trigger: Trigger<Fired<ToggleFlyCam>>,
     mut commands: Commands,
+    mut movement_consumer: ResMut<NextState<ActiveMovementConsumer>>,
     targets: Query<&TargetedBy>,
     cameras: Query<(Entity, Has<FlyingCamera>), With<Camera>>,
 ) {
@@ -103,14 +163,14 @@ fn toggle_fly_cam(
         if let Ok((camera, is_fly_camera)) = cameras.get(target.0) {
             match is_fly_camera {
                 true => {
-                    commands.entity(trigger.target()).remove::<Frozen>();
+                    movement_consumer.set(ActiveMovementConsumer::Character);
                     commands
                         .entity(camera)
                         .remove::<FlyingCamera>()
                         .insert(FollowOrigin::default());
                 }
                 false => {
-                    commands.entity(trigger.target()).insert(Frozen);
+                    movement_consumer.set(ActiveMovementConsumer::FlyCamera);
                     commands
                         .entity(camera)
                         .remove::<FollowOrigin>()
@@ -122,13 +182,16 @@ fn toggle_fly_cam(
 }
 
 fn view_input(
-    mut cameras: Query<(&mut ViewAngles, &mut Transform, &Sensitivity)>,
-    actions: Single<&Actions<DefaultContext>>,
+    mut cameras: Query<(&mut ViewAngles, &mut Transform, &Sensitivity, &Targeting)>,
+    targets: Query<(&Actions<DefaultContext>, Option<&CharacterUp>)>,
     time: Res<Time>,
 ) {
-    let actions = actions.into_inner();
+    // Every camera is controlled by the input of it's own target
+    for (mut angles, mut transform, sensitivity, targeting) in &mut cameras {
+        let Ok((actions, up)) = targets.get(targeting.0) else {
+            continue;
+        };
 
-    for (mut angles, mut transform, sensitivity) in &mut cameras {
         let orbit_input = actions.value::<Look>().unwrap_or_default() * sensitivity.0;
         let angle_deltas = orbit_input * PI * time.delta_secs();
 
@@ -136,27 +199,203 @@ fn view_input(
         angles.pitch = angles.pitch.clamp(-PI / 2.0 + 0.01, PI / 2.0 - 0.01);
         angles.yaw += angle_deltas.x;
 
-        transform.rotation = angles.to_quat();
+        transform.rotation = angles.to_quat_with_up(up.map_or(Dir3::Y, |up| up.0));
+    }
+}
+
+fn face_camera_yaw(
+    cameras: Query<(&ViewAngles, &Targeting), With<FirstPersonCamera>>,
+    mut targets: Query<(&mut Transform, Option<&CharacterUp>), With<FaceCameraYaw>>,
+) {
+    for (angles, targeting) in &cameras {
+        let Ok((mut transform, up)) = targets.get_mut(targeting.0) else {
+            continue;
+        };
+
+        let up = up.map_or(Dir3::Y, |up| up.0);
+        transform.rotation =
+            Quat::from_rotation_arc(Vec3::Y, *up) * Quat::from_rotation_y(angles.yaw);
     }
 }
 
 fn update_origin(
     targets: Query<&GlobalTransform>,
+    children: Query<&Children>,
+    sockets: Query<(), With<CameraSocket>>,
+    ups: Query<&CharacterUp>,
     mut cameras: Query<(
         &mut FollowOrigin,
         &mut Transform,
         &ViewAngles,
-        &FollowOffset,
+        &mut FollowOffset,
         &Targeting,
+        Option<&mut FollowSmoothing>,
     )>,
+    time: Res<Time>,
 ) {
-    for (mut origin, mut transform, angles, offset, targeting) in &mut cameras {
-        if let Ok(orbit_transform) = targets.get(targeting.0) {
+    for (mut origin, mut transform, angles, mut offset, targeting, smoothing) in &mut cameras {
+        if let Some(target_absolute) = offset.target_absolute {
+            let t = 1.0 - f32::exp(-offset.lerp_speed * time.delta_secs());
+            offset.absolute = offset.absolute.lerp(target_absolute, t);
+        }
+
+        // Follow the socket of the target if it has one
+        let target = children
+            .iter_descendants(targeting.0)
+            .find(|&entity| sockets.contains(entity))
+            .unwrap_or(targeting.0);
+
+        if let Ok(orbit_transform) = targets.get(target) {
+            // Both offsets are relative to the `up` of the target, so the camera follows it onto walls and planets
+            let up = ups.get(targeting.0).map_or(Dir3::Y, |up| up.0);
             let mut point = orbit_transform.translation();
-            point += offset.absolute;
-            point += angles.to_quat() * offset.relative;
-            origin.0 = point;
-            transform.translation = point;
+            point += Quat::from_rotation_arc(Vec3::Y, *up) * offset.absolute;
+            point += angles.to_quat_with_up(up) * offset.relative;
+
+            origin.0 = match smoothing {
+                Some(mut smoothing) if smoothing.snap || origin.is_added() => {
+                    smoothing.snap = false;
+                    point
+                }
+                Some(smoothing) => {
+                    let t = 1.0 - f32::exp(-smoothing.speed * time.delta_secs());
+                    origin.0.lerp(point, t)
+                }
+                None => point,
+            };
+
+            transform.translation = origin.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cameras_follow_the_up_of_their_own_target() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_systems(Update, update_origin);
+
+        let floor_target = app
+            .world_mut()
+            .spawn((GlobalTransform::IDENTITY, CharacterUp(Dir3::Y)))
+            .id();
+        let wall_target = app
+            .world_mut()
+            .spawn((
+                GlobalTransform::from_translation(Vec3::new(10.0, 0.0, 0.0)),
+                CharacterUp(Dir3::NEG_X),
+            ))
+            .id();
+
+        let offset = FollowOffset {
+            absolute: Vec3::Y * 2.0,
+            ..default()
+        };
+        let floor_camera = app
+            .world_mut()
+            .spawn((
+                FollowOrigin::default(),
+                offset,
+                ViewAngles::default(),
+                Transform::default(),
+                Targeting(floor_target),
+            ))
+            .id();
+        let wall_camera = app
+            .world_mut()
+            .spawn((
+                FollowOrigin::default(),
+                offset,
+                ViewAngles::default(),
+                Transform::default(),
+                Targeting(wall_target),
+            ))
+            .id();
+
+        app.update();
+
+        let floor_translation = app
+            .world()
+            .get::<Transform>(floor_camera)
+            .unwrap()
+            .translation;
+        let wall_translation = app
+            .world()
+            .get::<Transform>(wall_camera)
+            .unwrap()
+            .translation;
+
+        assert!(
+            floor_translation.distance(Vec3::new(0.0, 2.0, 0.0)) < 1e-4,
+            "{floor_translation}"
+        );
+        assert!(
+            wall_translation.distance(Vec3::new(8.0, 0.0, 0.0)) < 1e-4,
+            "{wall_translation}"
+        );
+    }
+
+    #[test]
+    fn first_person_camera_turns_the_target_with_its_yaw() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_systems(Update, face_camera_yaw);
+
+        let floor_target = app
+            .world_mut()
+            .spawn((Transform::default(), FaceCameraYaw))
+            .id();
+        let wall_target = app
+            .world_mut()
+            .spawn((Transform::default(), FaceCameraYaw, CharacterUp(Dir3::X)))
+            .id();
+        let third_person_target = app
+            .world_mut()
+            .spawn((Transform::default(), FaceCameraYaw))
+            .id();
+
+        let cameras = [
+            app.world_mut()
+                .spawn((
+                    ViewAngles::default(),
+                    Targeting(floor_target),
+                    FirstPersonCamera,
+                ))
+                .id(),
+            app.world_mut()
+                .spawn((
+                    ViewAngles::default(),
+                    Targeting(wall_target),
+                    FirstPersonCamera,
+                ))
+                .id(),
+            app.world_mut()
+                .spawn((ViewAngles::default(), Targeting(third_person_target)))
+                .id(),
+        ];
+
+        // Look around, the pitch must not tilt the targets
+        for (yaw, pitch) in [(1.0, 0.5), (-2.0, -1.0)] {
+            for camera in cameras {
+                let mut angles = app.world_mut().get_mut::<ViewAngles>(camera).unwrap();
+                angles.yaw = yaw;
+                angles.pitch = pitch;
+            }
+            app.update();
+
+            let rotation = |entity| app.world().get::<Transform>(entity).unwrap().rotation;
+
+            assert!(rotation(floor_target).abs_diff_eq(Quat::from_rotation_y(yaw), 1e-4));
+            assert!(rotation(wall_target).abs_diff_eq(
+                Quat::from_rotation_arc(Vec3::Y, Vec3::X) * Quat::from_rotation_y(yaw),
+                1e-4
+            ));
+            assert!((rotation(wall_target) * Vec3::Y).abs_diff_eq(Vec3::X, 1e-4));
+            assert_eq!(rotation(third_person_target), Quat::IDENTITY);
         }
     }
 }
@@ -1,7 +1,10 @@
 use super::{FollowOrigin, MainCamera, TargetedBy, Targeting};
-use crate::input::{OrbitCameraContext, OrbitZoom};
+use crate::{
+    CharacterMotion, CharacterUp,
+    input::{OrbitCameraContext, OrbitZoom},
+};
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{prelude::*, render::camera::CameraProjection};
 use bevy_enhanced_input::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
@@ -9,17 +12,53 @@ pub(super) fn plugin(app: &mut App) {
         RunFixedMainLoop,
         zoom_input.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
     );
-    app.add_systems(Update, update_spring_arm.after(super::update_origin));
+    app.add_systems(
+        Update,
+        (update_spring_arm, head_bob)
+            .chain()
+            .after(super::update_origin),
+    );
+
+    #[cfg(feature = "debug")]
+    app.init_gizmo_group::<SpringArmGizmos>().add_systems(
+        PostUpdate,
+        draw_spring_arm_gizmos.before(TransformSystem::TransformPropagate),
+    );
 }
 
+/// Keeps the camera at a distance from the followed origin, pulling it in when something is in the way.
 #[derive(Component, Reflect, Debug, Clone, Copy)]
 #[reflect(Component)]
-pub(super) struct SpringArm {
+pub struct SpringArm {
     pub distance: f32,
     pub target_distance: f32,
     pub recover_speed: f32,
+    /// How fast the arm is pulled in towards a collision, it snaps in when the collision is much closer than the current distance.
+    pub collapse_speed: f32,
     pub collision_radius: f32,
+    /// The fraction of the current distance zoomed per unit of zoom input.
+    pub zoom_speed: f32,
+    /// The range the zoom input can change the target distance in.
+    pub min_distance: f32,
+    pub max_distance: f32,
     pub filters: LayerMask,
+    /// The point the arm collided with during the last update, if any.
+    pub hit_point: Option<Vec3>,
+    /// The distance from the origin to the collision during the last update, if any.
+    pub hit_distance: Option<f32>,
+    /// The number of extra probes cast from the corners of the camera's near plane, at most 4.
+    ///
+    /// The closest hit of all probes is used, which avoids popping when the center probe slips past thin geometry like fences.
+    pub probe_count: usize,
+}
+
+impl SpringArm {
+    /// Returns `true` if something was between the origin and the target distance during the last update.
+    ///
+    /// Combined with the [`SpringArm::distance`] this can be used to fade out the character when the camera gets too close.
+    pub fn is_occluded(&self) -> bool {
+        self.hit_distance.is_some()
+    }
 }
 
 impl Default for SpringArm {
@@ -28,12 +67,56 @@ impl Default for SpringArm {
             distance: 4.0,
             target_distance: 4.0,
             recover_speed: 6.0,
+            collapse_speed: 30.0,
             collision_radius: 0.1,
+            zoom_speed: 0.1,
+            min_distance: 0.1,
+            max_distance: 100.0,
             filters: LayerMask::ALL,
+            hit_point: None,
+            hit_distance: None,
+            probe_count: 0,
+        }
+    }
+}
+
+/// Bobs a [`FirstPersonCamera`] up and down and side to side while the targeted character walks.
+///
+/// The offset is added on top of the [`FollowOffset`](super::FollowOffset) and fades out when the character stops or leaves the ground.
+/// The target needs a [`CharacterMotion`] to bob.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct HeadBob {
+    /// The vertical offset at full speed, the lateral offset is half of it.
+    pub amplitude: f32,
+    /// The number of bobs per distance travelled, so faster characters bob faster.
+    pub frequency: f32,
+    /// The current phase of the bob in radians.
+    pub phase: f32,
+    /// The horizontal speed at which the full `amplitude` is reached.
+    pub full_speed: f32,
+    /// How quickly the bob fades in and out.
+    pub fade_speed: f32,
+    /// The current strength of the bob in `[0, 1]`.
+    weight: f32,
+}
+
+impl Default for HeadBob {
+    fn default() -> Self {
+        Self {
+            amplitude: 0.05,
+            frequency: 0.35,
+            phase: 0.0,
+            full_speed: 8.0,
+            fade_speed: 8.0,
+            weight: 0.0,
         }
     }
 }
 
+/// The spring arm snaps in instead of smoothly collapsing when a collision is closer than this fraction of the current distance.
+const COLLAPSE_SNAP_RATIO: f32 = 0.5;
+
 #[derive(Component, Reflect, Default, Debug, Clone, Copy)]
 #[reflect(Component)]
 pub(super) struct FirstPersonCamera; // Used for toggling the spring arm distance without removing it
@@ -45,9 +128,11 @@ pub(super) fn zoom_input(
     for (actions, targeted_by) in &targets {
         if let Ok(mut arm) = cameras.get_mut(targeted_by.0) {
             let zoom_input = actions.value::<OrbitZoom>().unwrap_or_default();
-            let zoom_delta = zoom_input.y * arm.distance * 0.1; // TODO: configurable speed
+            let zoom_delta = zoom_input.y * arm.distance * arm.zoom_speed;
             arm.target_distance -= zoom_delta;
-            arm.target_distance = arm.target_distance.clamp(0.1, 100.0); // TODO: configurable range
+            arm.target_distance = arm
+                .target_distance
+                .clamp(arm.min_distance, arm.max_distance);
         }
     }
 }
@@ -57,36 +142,60 @@ pub(super) fn update_spring_arm(
     mut cameras: Query<(
         &mut SpringArm,
         &mut Transform,
+        &Projection,
         &FollowOrigin,
         &Targeting,
         Has<FirstPersonCamera>,
     )>,
     time: Res<Time>,
 ) {
-    for (mut arm, mut camera_transform, origin, attached_to, first_person) in &mut cameras {
+    for (mut arm, mut camera_transform, projection, origin, attached_to, first_person) in
+        &mut cameras
+    {
         let direction = camera_transform.rotation * Dir3::Z;
 
         let filter =
             SpatialQueryFilter::from_mask(arm.filters).with_excluded_entities([attached_to.0]);
+        let probe = Collider::sphere(arm.collision_radius);
+
+        arm.hit_point = None;
+        arm.hit_distance = None;
 
         // Smoothly interpolate to an arm distance of 0.0 when in first person mode
         if first_person {
             arm.distance = arm
                 .distance
                 .lerp(0.0, arm.recover_speed * time.delta_secs());
-        } else if let Some(hit) = spatial_query.cast_shape(
-            &Collider::sphere(arm.collision_radius),
-            origin.0,
-            Quat::IDENTITY,
-            direction,
-            &ShapeCastConfig {
-                max_distance: arm.target_distance,
-                ..Default::default()
-            },
-            &filter,
-        ) {
-            // If there's a collision, quickly snap to the hit distance to avoid clipping with the world
-            arm.distance = hit.distance;
+        } else if let Some(hit) = probe_offsets(projection, arm.probe_count)
+            .into_iter()
+            .filter_map(|offset| {
+                spatial_query.cast_shape(
+                    &probe,
+                    origin.0 + camera_transform.rotation * offset,
+                    Quat::IDENTITY,
+                    direction,
+                    &ShapeCastConfig {
+                        max_distance: arm.target_distance,
+                        ..Default::default()
+                    },
+                    &filter,
+                )
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+        {
+            // If there's a collision, quickly pull in to the hit distance to avoid clipping with the world
+            arm.distance = if hit.distance < arm.distance * COLLAPSE_SNAP_RATIO {
+                hit.distance
+            } else if hit.distance < arm.distance {
+                let t = (arm.collapse_speed * time.delta_secs()).min(1.0);
+                arm.distance.lerp(hit.distance, t)
+            } else {
+                arm.distance
+                    .lerp(hit.distance, arm.recover_speed * time.delta_secs())
+                    .min(hit.distance)
+            };
+            arm.hit_point = Some(hit.point1);
+            arm.hit_distance = Some(hit.distance);
         } else {
             // Otherwise, interpolate to the target distance
             let distance = arm
@@ -98,3 +207,93 @@ pub(super) fn update_spring_arm(
         camera_transform.translation = origin.0 + direction * arm.distance;
     }
 }
+
+fn head_bob(
+    mut cameras: Query<
+        (&mut HeadBob, &mut Transform, &Targeting),
+        (With<FirstPersonCamera>, With<FollowOrigin>),
+    >,
+    targets: Query<(&CharacterMotion, Option<&CharacterUp>)>,
+    time: Res<Time>,
+) {
+    for (mut bob, mut transform, targeting) in &mut cameras {
+        let Ok((motion, up)) = targets.get(targeting.0) else {
+            continue;
+        };
+
+        let up = up.map_or(Dir3::Y, |up| up.0);
+        let speed = motion.velocity.reject_from_normalized(*up).length();
+
+        // Fade out smoothly instead of snapping back when stopping or leaving the ground
+        let target_weight = match motion.grounded {
+            true => (speed / bob.full_speed).min(1.0),
+            false => 0.0,
+        };
+        let t = 1.0 - f32::exp(-bob.fade_speed * time.delta_secs());
+        bob.weight = bob.weight.lerp(target_weight, t);
+
+        bob.phase += std::f32::consts::TAU * bob.frequency * speed * time.delta_secs();
+        bob.phase %= std::f32::consts::TAU;
+
+        // The vertical bob has twice the frequency of the lateral one, once per step
+        let right = transform.rotation * Vec3::X;
+        let offset = *up * (bob.phase * 2.0).sin() * bob.amplitude
+            + right * bob.phase.sin() * bob.amplitude / 2.0;
+
+        transform.translation += offset * bob.weight;
+    }
+}
+
+/// Returns the start offsets of the spring arm probes relative to the camera, the center followed by up to
+/// `probe_count` corners of the near plane of the `projection`.
+fn probe_offsets(projection: &Projection, probe_count: usize) -> Vec<Vec3> {
+    let near = match projection {
+        Projection::Perspective(perspective) => perspective.near,
+        Projection::Orthographic(orthographic) => orthographic.near,
+        // The near plane of custom projections is unknown, only use the center probe
+        Projection::Custom(_) => return vec![Vec3::ZERO],
+    };
+
+    // The corners are in view space, only their offset perpendicular to the view direction matters
+    let corners = projection.get_frustum_corners(-near, -near);
+
+    std::iter::once(Vec3::ZERO)
+        .chain(
+            corners
+                .into_iter()
+                .take(probe_count.min(4))
+                .map(|corner| Vec3::new(corner.x, corner.y, 0.0)),
+        )
+        .collect()
+}
+
+/// Gizmos of the [`SpringArm`], toggle them using the [`GizmoConfigStore`].
+#[cfg(feature = "debug")]
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct SpringArmGizmos;
+
+/// Draws the target distance of the arm in gray, the actual distance in green and the hit point in red.
+#[cfg(feature = "debug")]
+fn draw_spring_arm_gizmos(
+    mut gizmos: Gizmos<SpringArmGizmos>,
+    cameras: Query<(&SpringArm, &Transform, &FollowOrigin)>,
+) {
+    use bevy::color::palettes::css::{GRAY, LIME, RED};
+
+    for (arm, camera_transform, origin) in &cameras {
+        let direction = camera_transform.rotation * Dir3::Z;
+        let target = origin.0 + direction * arm.target_distance;
+
+        gizmos.line(origin.0, target, GRAY);
+        gizmos.line(origin.0, camera_transform.translation, LIME);
+        gizmos.sphere(
+            Isometry3d::from_translation(camera_transform.translation),
+            arm.collision_radius,
+            LIME,
+        );
+
+        if let Some(hit_point) = arm.hit_point {
+            gizmos.sphere(Isometry3d::from_translation(hit_point), 0.05, RED);
+        }
+    }
+}
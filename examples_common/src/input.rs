@@ -18,6 +18,26 @@ pub struct Look; // Camera look/rotation
 #[input_action(output = bool)]
 pub struct Jump;
 
+#[derive(Debug, Clone, Copy, InputAction)]
+#[input_action(output = bool)]
+pub struct ReleaseJump; // Releasing the jump input early cuts the jump short
+
+#[derive(Debug, Clone, Copy, InputAction)]
+#[input_action(output = bool)]
+pub struct Dash;
+
+#[derive(Debug, Clone, Copy, InputAction)]
+#[input_action(output = bool)]
+pub struct Crouch; // Held to stay crouched
+
+#[derive(Debug, Clone, Copy, InputAction)]
+#[input_action(output = bool)]
+pub struct DumpState; // Log the full state of the character for bug reports
+
+#[derive(Debug, Clone, Copy, InputAction)]
+#[input_action(output = bool)]
+pub struct Phase; // Temporarily move through walls
+
 #[derive(Debug, Clone, Copy, InputAction)]
 #[input_action(output = bool)]
 pub(super) struct CaptureCursor;
@@ -38,7 +58,11 @@ pub(super) struct ToggleFlyCam;
 
 #[derive(InputAction, Debug, Clone, Copy)]
 #[input_action(output = f32)]
-pub(super) struct Fly;
+pub struct Fly; // Vertical movement, also used by swimming characters
+
+#[derive(Debug, Clone, Copy, InputAction)]
+#[input_action(output = bool)]
+pub(super) struct FlySprint; // Multiplies the fly speed while held
 
 // --- Orbit Camera Specific Actions  ---
 #[derive(Debug, Clone, Copy, InputAction)]
@@ -53,7 +77,7 @@ pub struct DefaultContext;
 
 /// Context for Fly Camera specific controls.
 #[derive(InputContext, Default)]
-pub(super) struct FlyCameraContext;
+pub struct FlyCameraContext;
 
 /// Context for Orbit Camera specific controls.
 #[derive(InputContext, Default)]
@@ -107,6 +131,10 @@ fn bind_default_context_actions(
             .bind::<Move>()
             .to((Cardinal::wasd_keys(), Axial::left_stick()))
             .with_modifiers(DeadZone::default()); // Keep existing modifiers if needed
+        actions
+            .bind::<Phase>()
+            .to((KeyCode::KeyV, GamepadButton::North))
+            .with_conditions(Press::default());
 
         actions.bind::<CaptureCursor>().to(MouseButton::Left);
         actions.bind::<ReleaseCursor>().to(KeyCode::Escape);
@@ -115,6 +143,17 @@ fn bind_default_context_actions(
             .bind::<Jump>()
             .to((KeyCode::Space, GamepadButton::East))
             .with_conditions(Press::default());
+        actions
+            .bind::<ReleaseJump>()
+            .to((KeyCode::Space, GamepadButton::East))
+            .with_conditions(Release::default());
+        actions
+            .bind::<Dash>()
+            .to((KeyCode::ShiftLeft, GamepadButton::West))
+            .with_conditions(Press::default());
+        actions
+            .bind::<Crouch>()
+            .to((KeyCode::ControlLeft, GamepadButton::RightThumb));
 
         // --- Camera Look (Used by FPS, potentially others if not overridden) ---
         actions.bind::<Look>().to((
@@ -132,6 +171,10 @@ fn bind_default_context_actions(
             .bind::<ToggleFlyCam>()
             .to((KeyCode::KeyF, GamepadButton::DPadUp))
             .with_conditions(Press::default());
+        actions
+            .bind::<DumpState>()
+            .to(KeyCode::F9)
+            .with_conditions(Press::default());
     } else {
         warn!(
             "Failed to get Actions<DefaultContext> for entity {:?} during binding",
@@ -163,6 +206,9 @@ fn bind_fly_camera_actions(
                 negative: GamepadButton::LeftThumb,
             },
         ));
+        actions
+            .bind::<FlySprint>()
+            .to((KeyCode::ShiftLeft, GamepadButton::LeftTrigger2));
     } else {
         warn!(
             "Failed to get Actions<FlyCameraContext> for entity {:?} during binding",
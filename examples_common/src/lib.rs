@@ -20,14 +20,37 @@ impl Plugin for ExampleCommonPlugin {
             PhysicsDiagnosticsPlugin,
             PhysicsDiagnosticsUiPlugin,
         ))
+        .init_state::<ActiveMovementConsumer>()
         .add_systems(Startup, setup);
     }
 }
 
-// Marker component used to freeze player movement when the main camera is in fly-mode.
-// This shouldn't be strictly necessary if we figure out how to properly layer InputContexts.
-#[derive(Component)]
-pub struct Frozen;
+/// Mirrors the `up` direction of a character so other systems (e.g. the camera) can align to it without accessing the character.
+#[derive(Component, Reflect, Debug, PartialEq, Clone, Copy)]
+#[reflect(Component)]
+pub struct CharacterUp(pub Dir3);
+
+impl Default for CharacterUp {
+    fn default() -> Self {
+        Self(Dir3::Y)
+    }
+}
+
+/// Mirrors the velocity and grounded state of a character so other systems (e.g. the head bob) can read them without accessing the character.
+#[derive(Component, Reflect, Default, Debug, PartialEq, Clone, Copy)]
+#[reflect(Component)]
+pub struct CharacterMotion {
+    pub velocity: Vec3,
+    pub grounded: bool,
+}
+
+/// The consumer of the shared movement actions (e.g. [`input::Move`]), only the active one should read them.
+#[derive(States, Default, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ActiveMovementConsumer {
+    #[default]
+    Character,
+    FlyCamera,
+}
 
 fn setup(mut commands: Commands) {
     // Sun
@@ -11,6 +11,8 @@ use bevy::prelude::*;
 pub struct Ground {
     pub entity: Entity,
     pub normal: Dir3,
+    /// The world-space point where the character touches the ground.
+    pub point: Vec3,
 }
 
 impl Ground {
@@ -18,6 +20,7 @@ impl Ground {
     pub fn new_if_walkable(
         entity: Entity,
         normal: impl TryInto<Dir3>,
+        point: Vec3,
         up: Dir3,
         walkable_angle: f32,
     ) -> Option<Self> {
@@ -27,7 +30,11 @@ impl Ground {
             return None;
         }
 
-        Some(Self { entity, normal })
+        Some(Self {
+            entity,
+            normal,
+            point,
+        })
     }
 
     /// Returns `true` if the [`Ground`] is walkable with the given `walkable_angle` and `up` direction.
@@ -36,12 +43,155 @@ impl Ground {
     }
 }
 
+/// Overrides the walkable angle of characters (in radians) when standing on this collider.
+///
+/// Use a higher angle for grippy surfaces and a lower angle for slippery ones like ice.
+#[derive(Component, Reflect, Debug, PartialEq, Clone, Copy)]
+#[reflect(Component)]
+pub struct SurfaceWalkability(pub f32);
+
+/// Returns the `walkable_angle` widened or narrowed by `margin` depending on whether the character is `grounded`.
+///
+/// This adds hysteresis to the walkable angle: a surface that was ground stays ground until it's clearly
+/// steeper than the walkable angle, and vice versa. Otherwise surfaces at exactly the walkable angle
+/// flicker between ground and wall as the contact normal jitters.
+pub fn walkable_angle_with_hysteresis(walkable_angle: f32, margin: f32, grounded: bool) -> f32 {
+    match grounded {
+        true => walkable_angle + margin,
+        false => walkable_angle - margin,
+    }
+}
+
 /// Checks if a surface is walkable based on its slope angle and the up direction.
 pub fn is_walkable(normal: Vec3, up: Dir3, walkable_angle: f32) -> bool {
     let slope_angle = up.angle_between(normal);
     slope_angle < walkable_angle
 }
 
+/// Checks if a surface is a ceiling based on its angle to the down direction, the opposite of `up`.
+///
+/// A `ceiling_angle` of 90° treats every surface facing down as a ceiling, including overhangs.
+pub fn is_ceiling(normal: Vec3, up: Dir3, ceiling_angle: f32) -> bool {
+    let overhead_angle = (-up).angle_between(normal);
+    overhead_angle < ceiling_angle
+}
+
+/// Returns `true` if the `ground` is only under the edge of the character's footprint, e.g. when walking off a ledge.
+///
+/// The ground is an edge when it's touched further than half the footprint `radius` away from the center and a ray
+/// from the center doesn't hit anything within `max_distance`.
+pub fn is_ledge_edge(
+    spatial_query: &SpatialQuery,
+    translation: Vec3,
+    up: Dir3,
+    ground: &Ground,
+    radius: f32,
+    max_distance: f32,
+    filter: &SpatialQueryFilter,
+) -> bool {
+    let offset = (ground.point - translation).reject_from_normalized(*up);
+
+    if offset.length_squared() < (radius / 2.0).powi(2) {
+        return false;
+    }
+
+    spatial_query
+        .cast_ray(translation, -up, max_distance, true, filter)
+        .is_none()
+}
+
+/// Returns the direction of steepest descent along a surface with the given `normal`.
+///
+/// This is the gravity direction (opposite of `up`) projected on the surface,
+/// returns `None` for surfaces that are (nearly) flat.
+pub fn downhill_direction(normal: Vec3, up: Dir3) -> Option<Dir3> {
+    let downhill = (-*up).reject_from(normal);
+
+    // The direction is meaningless on flat ground
+    if downhill.length_squared() < 1e-6 {
+        return None;
+    }
+
+    Dir3::new(downhill).ok()
+}
+
+/// Accelerates the `velocity` down a surface that is too steep to stand on, by the part of `gravity` along the surface.
+///
+/// This replaces the regular gravity while touching the surface, applying both would count the part along
+/// the surface twice. The `velocity` is returned unchanged for (nearly) flat surfaces.
+pub fn apply_slope_slide(
+    velocity: Vec3,
+    surface_normal: Vec3,
+    up: Dir3,
+    gravity: f32,
+    delta_time: f32,
+) -> Vec3 {
+    let Some(downhill) = downhill_direction(surface_normal, up) else {
+        return velocity;
+    };
+
+    // The part of the gravity along the surface grows with the steepness
+    let steepness = up.cross(surface_normal.normalize_or_zero()).length();
+
+    velocity + downhill * gravity * steepness * delta_time
+}
+
+/// Returns the velocity gained by redirecting a landing impact on a surface too steep to stand on down the slope.
+///
+/// `retention` is the fraction of the impact speed (the speed into the surface) that is kept. Nothing is redirected
+/// for walkable surfaces, which are landed on instead, or for surfaces that don't face up like walls and overhangs.
+pub fn steep_landing_velocity(
+    velocity: Vec3,
+    surface_normal: Vec3,
+    up: Dir3,
+    walkable_angle: f32,
+    retention: f32,
+) -> Vec3 {
+    let impact_speed = -velocity.dot(surface_normal);
+
+    if impact_speed <= 0.0
+        || surface_normal.dot(*up) <= 0.0
+        || is_walkable(surface_normal, up, walkable_angle)
+    {
+        return Vec3::ZERO;
+    }
+
+    downhill_direction(surface_normal, up)
+        .map_or(Vec3::ZERO, |downhill| downhill * impact_speed * retention)
+}
+
+/// Pulls a grounded `velocity` down a walkable slope by the part of `gravity` along the surface.
+///
+/// Slopes up to `hold_angle` (in radians) have enough friction to hold the character in place and the
+/// `velocity` is returned unchanged. On steeper slopes the character slides, but gravity never accelerates
+/// it down the slope past `max_slide_speed`.
+pub fn apply_slope_gravity(
+    velocity: Vec3,
+    surface_normal: Vec3,
+    up: Dir3,
+    gravity: f32,
+    hold_angle: f32,
+    max_slide_speed: f32,
+    delta_time: f32,
+) -> Vec3 {
+    if is_walkable(surface_normal, up, hold_angle) {
+        return velocity;
+    }
+
+    let Some(downhill) = downhill_direction(surface_normal, up) else {
+        return velocity;
+    };
+
+    let steepness = up.cross(surface_normal.normalize_or_zero()).length();
+
+    // Only accelerate up to the max slide speed, faster characters keep their speed
+    let downhill_speed = velocity.dot(*downhill);
+    let acceleration =
+        (max_slide_speed - downhill_speed).clamp(0.0, gravity * steepness * delta_time);
+
+    velocity + downhill * acceleration
+}
+
 /// Find and climb steps in the movement direction.
 ///
 /// # Prerequisites
@@ -68,6 +218,8 @@ pub fn is_walkable(normal: Vec3, up: Dir3, walkable_angle: f32) -> bool {
 /// 3. **Step Execution**
 ///    - Teleport up by the discovered height
 ///    - Move forward with remaining motion
+///
+/// Returns the new translation, the hit on top of the step and the height climbed along `up`.
 pub fn try_climb_step(
     spatial_query: &SpatialQuery,
     collider: &Collider,
@@ -78,10 +230,10 @@ pub fn try_climb_step(
     step_up_height: f32,
     epsilon: f32,
     filter: &SpatialQueryFilter,
-) -> Option<(Vec3, ShapeHitData)> {
+) -> Option<(Vec3, ShapeHitData, f32)> {
     let step_up_pos = translation + up * step_up_height;
 
-    let horizontal_motion = motion.reject_from_normalized(Vec3::Y);
+    let horizontal_motion = motion.reject_from_normalized(*up);
 
     // Only step up if horizontal motion is non zero
     if let Ok(direction) = Dir3::new(horizontal_motion) {
@@ -108,6 +260,7 @@ pub fn try_climb_step(
     let (safe_distance, step_down_hit) = sweep_check(
         collider,
         epsilon,
+        true,
         step_down_pos,
         -up,
         step_up_height,
@@ -117,11 +270,318 @@ pub fn try_climb_step(
     )?;
 
     let new_translation = step_down_pos - up * safe_distance;
+    let height = (new_translation - translation).dot(*up);
+
+    Some((new_translation, step_down_hit, height))
+}
+
+/// Find a lower step to snap down to when walking off the edge of a step.
+///
+/// This is meant for characters that were grounded and lost their ground while moving horizontally,
+/// e.g. when walking down stairs. The sweep only reaches down by `step_down_height`, so walking off
+/// a taller ledge still makes the character fall.
+///
+/// Returns the distance to move down along `up` and the walkable ground below, or `None` if there
+/// is no horizontal motion or no walkable ground within reach.
+pub fn try_step_down(
+    spatial_query: &SpatialQuery,
+    collider: &Collider,
+    translation: Vec3,
+    motion: Vec3,
+    rotation: Quat,
+    up: Dir3,
+    step_down_height: f32,
+    epsilon: f32,
+    filter: &SpatialQueryFilter,
+    walkable_angle: impl Fn(Entity) -> f32,
+) -> Option<(f32, Ground)> {
+    // Standing still at the edge of a step should never pull the character down
+    if motion.reject_from_normalized(*up).length_squared() < epsilon * epsilon {
+        return None;
+    }
+
+    let (safe_distance, hit) = sweep_check(
+        collider,
+        epsilon,
+        true,
+        translation,
+        -up,
+        step_down_height,
+        rotation,
+        spatial_query,
+        filter,
+    )?;
+
+    let ground = Ground::new_if_walkable(
+        hit.entity,
+        hit.normal1,
+        hit.point1,
+        up,
+        walkable_angle(hit.entity),
+    )?;
+
+    Some((safe_distance, ground))
+}
+
+/// Returns how far a capsule has to move into a ledge for the rounded bottom to land on top of it.
+///
+/// The normal of the ledge edge changes depending on how far out on it the capsule is, so it has to
+/// move in far enough for the normal to be walkable.
+pub fn capsule_ledge_inward(radius: f32, walkable_angle: f32, epsilon: f32) -> f32 {
+    radius * (1.0 - walkable_angle.cos()) + epsilon * std::f32::consts::PI
+}
+
+/// The shape of a character collider, answers the questions about the bottom of the shape that stepping needs.
+#[derive(Reflect, Debug, PartialEq, Clone, Copy)]
+pub enum CharacterShape {
+    /// A capsule with a rounded bottom, the `length` excludes the hemispheres.
+    Capsule { radius: f32, length: f32 },
+    /// A cylinder with a flat bottom.
+    Cylinder { radius: f32, height: f32 },
+}
+
+impl CharacterShape {
+    /// Returns the shape of the `collider`, or `None` if it's neither a capsule nor a cylinder.
+    pub fn from_collider(collider: &Collider) -> Option<Self> {
+        let shape = collider.shape_scaled();
+
+        if let Some(capsule) = shape.as_capsule() {
+            return Some(Self::Capsule {
+                radius: capsule.radius,
+                length: capsule.half_height() * 2.0,
+            });
+        }
+
+        shape.as_cylinder().map(|cylinder| Self::Cylinder {
+            radius: cylinder.radius,
+            height: cylinder.half_height * 2.0,
+        })
+    }
+
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Self::Capsule { radius, .. } | Self::Cylinder { radius, .. } => radius,
+        }
+    }
+
+    /// Returns the total height of the shape along it's axis.
+    pub fn height(&self) -> f32 {
+        match *self {
+            Self::Capsule { radius, length } => length + radius * 2.0,
+            Self::Cylinder { height, .. } => height,
+        }
+    }
+
+    /// Returns a cylinder with the same radius and height, e.g. to step up with a flat bottom.
+    pub fn flat_bottomed(&self) -> Self {
+        Self::Cylinder {
+            radius: self.radius(),
+            height: self.height(),
+        }
+    }
+
+    /// Returns how far the shape has to move into a ledge to land on top of it.
+    ///
+    /// This is [`capsule_ledge_inward`] for capsules, cylinders only move in by the `epsilon` margin
+    /// since their flat bottom lands on the ledge right away.
+    pub fn ledge_inward(&self, walkable_angle: f32, epsilon: f32) -> f32 {
+        match *self {
+            Self::Capsule { radius, .. } => capsule_ledge_inward(radius, walkable_angle, epsilon),
+            Self::Cylinder { .. } => epsilon * std::f32::consts::PI,
+        }
+    }
+
+    pub fn collider(&self) -> Collider {
+        match *self {
+            Self::Capsule { radius, length } => Collider::capsule(radius, length),
+            Self::Cylinder { radius, height } => Collider::cylinder(radius, height),
+        }
+    }
+}
+
+/// Tries to pull the character up onto a ledge that is too tall to step up, e.g. when jumping into a wall.
+///
+/// The character is swept up by at most `mantle_reach`, then `inward` into the wall (see [`capsule_ledge_inward`])
+/// and back down onto the top of the ledge.
+///
+/// Returns the translation on top of the ledge and the ground there, or `None` if there's no walkable ground
+/// within reach or the collider doesn't fit above the ledge.
+pub fn try_mantle(
+    spatial_query: &SpatialQuery,
+    collider: &Collider,
+    translation: Vec3,
+    rotation: Quat,
+    up: Dir3,
+    wall_normal: Vec3,
+    mantle_reach: f32,
+    inward: f32,
+    epsilon: f32,
+    filter: &SpatialQueryFilter,
+    walkable_angle: impl Fn(Entity) -> f32,
+) -> Option<(Vec3, Ground)> {
+    let into_wall = Dir3::new(-wall_normal.reject_from_normalized(*up)).ok()?;
+
+    // Rise as far as the ceiling allows
+    let rise = sweep_check(
+        collider,
+        epsilon,
+        true,
+        translation,
+        up,
+        mantle_reach,
+        rotation,
+        spatial_query,
+        filter,
+    )
+    .map_or(mantle_reach, |(safe_distance, _)| safe_distance);
+
+    // There's no room to rise above anything, e.g. when pressed against a ceiling
+    if rise <= 0.0 {
+        return None;
+    }
+
+    let top = translation + up * rise;
+
+    // The wall continues above the reach, there's no ledge to grab
+    if sweep_check(
+        collider,
+        epsilon,
+        true,
+        top,
+        into_wall,
+        inward,
+        rotation,
+        spatial_query,
+        filter,
+    )
+    .is_some()
+    {
+        return None;
+    }
+
+    let above_ledge = top + into_wall * inward;
+
+    // Find the top of the ledge
+    let (safe_distance, hit) = sweep_check(
+        collider,
+        epsilon,
+        true,
+        above_ledge,
+        -up,
+        rise,
+        rotation,
+        spatial_query,
+        filter,
+    )?;
+
+    let ground = Ground::new_if_walkable(
+        hit.entity,
+        hit.normal1,
+        hit.point1,
+        up,
+        walkable_angle(hit.entity),
+    )?;
 
-    Some((new_translation, step_down_hit))
+    let new_translation = above_ledge - up * safe_distance;
+
+    // Make sure the collider fits on top of the ledge
+    spatial_query
+        .shape_intersections(collider, new_translation, rotation, filter)
+        .is_empty()
+        .then_some((new_translation, ground))
+}
+
+/// The number of rings of candidates tried around the origin by [`find_spawn_position`].
+const SPAWN_SEARCH_RINGS: usize = 4;
+/// The number of candidates per ring tried by [`find_spawn_position`].
+const SPAWN_SEARCH_RING_SAMPLES: usize = 8;
+
+/// Finds a position near `near` where the `collider` rests on walkable ground without penetrating anything.
+///
+/// Candidates are tried in rings around `near` up to `search_radius` away, closest first. For every candidate
+/// the ground is searched for by sweeping down from `search_radius` above it to `search_radius` below it.
+///
+/// Returns `None` if no valid position is found within the `search_radius`.
+pub fn find_spawn_position(
+    spatial_query: &SpatialQuery,
+    collider: &Collider,
+    config: MoveAndSlideConfig,
+    near: Vec3,
+    up: Dir3,
+    rotation: Quat,
+    filter: &SpatialQueryFilter,
+    search_radius: f32,
+    walkable_angle: impl Fn(Entity) -> f32,
+) -> Option<Vec3> {
+    let (a, b) = up.any_orthonormal_pair();
+
+    let rings = (1..=SPAWN_SEARCH_RINGS).flat_map(|ring| {
+        let radius = search_radius * ring as f32 / SPAWN_SEARCH_RINGS as f32;
+
+        (0..SPAWN_SEARCH_RING_SAMPLES).map(move |sample| {
+            let angle = std::f32::consts::TAU * sample as f32 / SPAWN_SEARCH_RING_SAMPLES as f32;
+            (a * angle.cos() + b * angle.sin()) * radius
+        })
+    });
+
+    std::iter::once(Vec3::ZERO).chain(rings).find_map(|offset| {
+        let start = near + offset + up * search_radius;
+
+        // Starting inside of something would make the sweep skip it
+        if !spatial_query
+            .shape_intersections(collider, start, rotation, filter)
+            .is_empty()
+        {
+            return None;
+        }
+
+        let (safe_distance, _) = ground_check(
+            collider,
+            config,
+            start,
+            up,
+            rotation,
+            spatial_query,
+            filter,
+            GroundCheckParams::fixed(search_radius * 2.0),
+            &walkable_angle,
+        )?;
+
+        let position = start - up * safe_distance;
+
+        // Make sure there's room for the collider at the resting position
+        spatial_query
+            .shape_intersections(collider, position, rotation, filter)
+            .is_empty()
+            .then_some(position)
+    })
+}
+
+/// How far [`ground_check`] looks for ground below the character.
+#[derive(Reflect, Default, Debug, PartialEq, Clone, Copy)]
+pub struct GroundCheckParams {
+    pub floor_check_distance: f32,
+    /// The velocity of the character, only the horizontal speed is used to extend the sweep.
+    pub velocity: Vec3,
+    pub delta_time: f32,
+    /// The sweep reaches `speed_scaled_extra * horizontal_speed * delta_time` further than the `floor_check_distance`,
+    /// so fast characters can stay on downhill slopes. Use `0.0` to only sweep the `floor_check_distance`.
+    pub speed_scaled_extra: f32,
+}
+
+impl GroundCheckParams {
+    /// Only sweep `floor_check_distance`, regardless of the speed of the character.
+    pub fn fixed(floor_check_distance: f32) -> Self {
+        Self {
+            floor_check_distance,
+            ..Default::default()
+        }
+    }
 }
 
 /// Sweep in the opposite direction of `up` and return the [`Ground`] if it's walkable.
+///
+/// `walkable_angle` should return the walkable angle for the hit entity, see [`SurfaceWalkability`].
 pub fn ground_check(
     collider: &Collider,
     config: MoveAndSlideConfig,
@@ -130,25 +590,71 @@ pub fn ground_check(
     rotation: Quat,
     spatial_query: &SpatialQuery,
     filter: &SpatialQueryFilter,
-    floor_check_distance: f32,
-    walkable_angle: f32,
+    params: GroundCheckParams,
+    walkable_angle: impl Fn(Entity) -> f32,
 ) -> Option<(f32, Ground)> {
+    let GroundCheckParams {
+        floor_check_distance,
+        velocity,
+        delta_time,
+        speed_scaled_extra,
+    } = params;
+    let horizontal_speed = velocity.reject_from_normalized(*up).length();
+
     let (safe_distance, hit) = sweep_check(
         collider,
         config.epsilon,
+        config.ignore_origin_penetration,
         translation,
         -up,
-        floor_check_distance,
+        floor_check_distance + speed_scaled_extra * horizontal_speed * delta_time,
         rotation,
         spatial_query,
         filter,
     )?;
 
-    let ground = Ground::new_if_walkable(hit.entity, hit.normal1, up, walkable_angle)?;
+    let ground = Ground::new_if_walkable(
+        hit.entity,
+        hit.normal1,
+        hit.point1,
+        up,
+        walkable_angle(hit.entity),
+    )?;
 
     Some((safe_distance, ground))
 }
 
+/// Like [`ground_check`], but also returns the velocity of the ground from `ground_velocity`.
+///
+/// `ground_velocity` should return the velocity of the body the hit entity belongs to, e.g. from it's
+/// [`LinearVelocity`]. This can be used to apply friction relative to moving ground like conveyor belts.
+pub fn ground_check_with_velocity(
+    collider: &Collider,
+    config: MoveAndSlideConfig,
+    translation: Vec3,
+    up: Dir3,
+    rotation: Quat,
+    spatial_query: &SpatialQuery,
+    filter: &SpatialQueryFilter,
+    params: GroundCheckParams,
+    walkable_angle: impl Fn(Entity) -> f32,
+    ground_velocity: impl Fn(Entity) -> Vec3,
+) -> Option<(f32, Ground, Vec3)> {
+    let (safe_distance, ground) = ground_check(
+        collider,
+        config,
+        translation,
+        up,
+        rotation,
+        spatial_query,
+        filter,
+        params,
+        walkable_angle,
+    )?;
+
+    Some((safe_distance, ground, ground_velocity(ground.entity)))
+}
+
 /// Projects a vector on a plane normal.
 ///
 /// The returned vector has different properties depending on whether the plane is walkable or not:
@@ -240,6 +746,26 @@ pub fn project_motion_on_wall(motion: Vec3, normal: impl TryInto<Dir3>, up: Dir3
     vertical + horizontal
 }
 
+/// Projects a vector on a ceiling plane.
+///
+/// Only the part of the vector going into the ceiling is removed, so pushing into a sloped ceiling
+/// redirects the motion along it (e.g. down into the floor) rather than stopping it.
+///
+/// **Panics** if the `normal` is zero, infinite or `NaN`.
+#[track_caller]
+pub fn project_motion_on_ceiling(motion: Vec3, normal: impl TryInto<Dir3>) -> Vec3 {
+    let normal = normal
+        .try_into()
+        .unwrap_or_else(|_| panic!("normal must not be zero, infinite or NaN"));
+
+    // Moving away from the ceiling, nothing to do
+    if motion.dot(*normal) >= 0.0 {
+        return motion;
+    }
+
+    motion.reject_from_normalized(*normal)
+}
+
 /// Transform a point that's relative to a previous transform to a new transform's space.
 ///
 /// Returns the new world-space position of the point.
@@ -258,7 +784,21 @@ pub fn transform_moving_point(
         .transform_point3(point_in_local_space)
 }
 
+/// Returns the angle in radians the `rotation` rotates about the `axis`, ignoring the rest of the rotation.
+///
+/// This is the twist of a swing-twist decomposition, e.g. the yaw of a rotation about `up`.
+pub fn twist_angle(rotation: Quat, axis: Dir3) -> f32 {
+    let projected = rotation.xyz().dot(*axis);
+    let angle = 2.0 * projected.atan2(rotation.w);
+
+    // Wrap into `-PI..PI` so the shortest rotation is returned
+    (angle + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
+
 /// Get the motion of a moving transform at the given `point`.
+///
+/// The point is moved through both transforms, so this includes the motion caused by rotation
+/// (e.g. being carried around on a spinning platform) and not just the translation.
 pub fn motion_on_point(
     point: Vec3,
     current_transform: &GlobalTransform,
@@ -266,3 +806,317 @@ pub fn motion_on_point(
 ) -> Vec3 {
     transform_moving_point(point, current_transform, previous_transform) - point
 }
+
+/// The movement intent of a character, e.g. decoded from the input actions every update.
+///
+/// This is separate from the velocity it produces, so it can be recorded or predicted on it's own.
+#[derive(Component, Reflect, Default, Debug, PartialEq, Clone, Copy)]
+#[reflect(Component)]
+pub struct CharacterInput {
+    /// The direction the character wants to move in.
+    pub wish_dir: Vec3,
+    /// The speed the character wants to move at.
+    pub wish_speed: f32,
+    pub jump: bool,
+    pub crouch: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{ecs::system::RunSystemOnce, scene::ScenePlugin, time::TimeUpdateStrategy};
+    use core::time::Duration;
+    use std::f32::consts::PI;
+
+    /// The normal of a slope that is `angle` radians steep.
+    fn slope_normal(angle: f32) -> Vec3 {
+        Quat::from_rotation_x(-angle) * Vec3::Y
+    }
+
+    #[test]
+    fn ceiling_redirects_motion_along_it() {
+        // A ceiling sloping down towards +z
+        let normal = Quat::from_rotation_x(PI / 4.0) * Vec3::NEG_Y;
+        let motion = project_motion_on_ceiling(Vec3::Z, normal);
+
+        assert!(motion.dot(normal).abs() < 1e-5);
+        assert!(motion.z > 0.0 && motion.y < 0.0);
+    }
+
+    #[test]
+    fn ceiling_keeps_motion_away_from_it() {
+        let normal = Quat::from_rotation_x(PI / 4.0) * Vec3::NEG_Y;
+        let motion = Vec3::new(1.0, -1.0, 0.0);
+
+        assert_eq!(project_motion_on_ceiling(motion, normal), motion);
+    }
+
+    #[test]
+    fn hysteresis_widens_the_walkable_angle_while_grounded() {
+        let margin = 3.0_f32.to_radians();
+
+        assert_eq!(
+            walkable_angle_with_hysteresis(PI / 4.0, margin, true),
+            PI / 4.0 + margin
+        );
+        assert_eq!(
+            walkable_angle_with_hysteresis(PI / 4.0, margin, false),
+            PI / 4.0 - margin
+        );
+    }
+
+    #[test]
+    fn hysteresis_keeps_slopes_near_the_walkable_angle_classified() {
+        let margin = 3.0_f32.to_radians();
+        // Jitters around the walkable angle
+        let normals = [44.0, 46.0].map(|angle: f32| slope_normal(angle.to_radians()));
+
+        // Ground stays ground and walls stay walls
+        for grounded in [true, false] {
+            let walkable_angle = walkable_angle_with_hysteresis(PI / 4.0, margin, grounded);
+
+            assert!(
+                normals
+                    .iter()
+                    .all(|normal| is_walkable(*normal, Dir3::Y, walkable_angle) == grounded)
+            );
+        }
+    }
+
+    #[test]
+    fn downhill_direction_points_down_the_slope() {
+        let normal = slope_normal(30.0_f32.to_radians());
+        let downhill = downhill_direction(normal, Dir3::Y).unwrap();
+
+        // Along the surface, going down towards the side the normal leans to
+        assert!(downhill.dot(normal).abs() < 1e-5);
+        assert!(downhill.y < 0.0);
+        assert!(downhill.x.abs() < 1e-5 && downhill.z * normal.z > 0.0);
+    }
+
+    #[test]
+    fn downhill_direction_is_none_on_flat_ground() {
+        assert_eq!(downhill_direction(Vec3::Y, Dir3::Y), None);
+    }
+
+    #[test]
+    fn motion_on_point_follows_a_rotating_platform() {
+        // A platform turning 90 degrees around a pivot at x = 1
+        let previous = GlobalTransform::from_translation(Vec3::X);
+        let current = GlobalTransform::from(
+            Transform::from_translation(Vec3::X).with_rotation(Quat::from_rotation_y(PI / 2.0)),
+        );
+
+        // A point 2 from the pivot is carried a quarter turn around it, from +x to -z
+        let motion = motion_on_point(Vec3::new(3.0, 0.5, 0.0), &current, &previous);
+        assert!(
+            motion.abs_diff_eq(Vec3::new(-2.0, 0.0, -2.0), 1e-5),
+            "{motion}"
+        );
+
+        // Points on the axis only turn in place
+        let motion = motion_on_point(Vec3::new(1.0, 2.0, 0.0), &current, &previous);
+        assert!(motion.abs_diff_eq(Vec3::ZERO, 1e-5), "{motion}");
+    }
+
+    #[test]
+    fn steep_landing_keeps_retained_speed_downhill() {
+        let normal = slope_normal(60.0_f32.to_radians());
+        let velocity = Vec3::NEG_Y * 10.0;
+
+        let gained = steep_landing_velocity(velocity, normal, Dir3::Y, PI / 4.0, 0.5);
+        let downhill = downhill_direction(normal, Dir3::Y).unwrap();
+        let impact_speed = -velocity.dot(normal);
+
+        assert!(gained.abs_diff_eq(*downhill * impact_speed * 0.5, 1e-5));
+        assert!(gained.dot(Vec3::Y) < 0.0);
+    }
+
+    #[test]
+    fn steep_landing_ignores_walls() {
+        let velocity = Vec3::new(-5.0, -10.0, 0.0);
+
+        assert_eq!(
+            steep_landing_velocity(velocity, Vec3::X, Dir3::Y, PI / 4.0, 0.5),
+            Vec3::ZERO
+        );
+    }
+
+    #[test]
+    fn steep_landing_ignores_walkable_slopes() {
+        let normal = slope_normal(30.0_f32.to_radians());
+
+        assert_eq!(
+            steep_landing_velocity(Vec3::NEG_Y * 10.0, normal, Dir3::Y, PI / 4.0, 0.5),
+            Vec3::ZERO
+        );
+    }
+
+    /// A headless app with physics, stepped once so the spatial query knows about the spawned colliders.
+    fn physics_app(spawn: impl FnOnce(&mut World)) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            // The collider constructors need the mesh and scene assets
+            AssetPlugin::default(),
+            ScenePlugin,
+            PhysicsPlugins::default(),
+        ));
+        app.init_asset::<Mesh>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 64.0,
+        )));
+        spawn(app.world_mut());
+        app.finish();
+        app.cleanup();
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        app
+    }
+
+    /// Spawns a slab with it's top `height` above the origin.
+    fn spawn_slab(world: &mut World, height: f32) {
+        world.spawn((
+            RigidBody::Static,
+            Collider::cuboid(8.0, 0.2, 8.0),
+            Transform::from_xyz(0.0, height - 0.1, 0.0),
+        ));
+    }
+
+    #[test]
+    fn flat_bottomed_shape_steps_up_to_the_ledge_height() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+            // A 0.2 tall ledge starting at x = 0
+            world.spawn((
+                RigidBody::Static,
+                Collider::cuboid(4.0, 0.2, 8.0),
+                Transform::from_xyz(2.0, 0.1, 0.0),
+            ));
+        });
+        let capsule = CharacterShape::Capsule {
+            radius: 0.35,
+            length: 1.0,
+        };
+        // Ends up with the center 0.2 in front of the ledge, only the edge of the bottom reaches over it
+        let climb = |app: &mut App, shape: CharacterShape| {
+            app.world_mut()
+                .run_system_once(move |spatial_query: SpatialQuery| {
+                    try_climb_step(
+                        &spatial_query,
+                        &shape.collider(),
+                        Vec3::new(-0.36, 0.86, 0.0),
+                        Vec3::X * 0.16,
+                        Quat::IDENTITY,
+                        Dir3::Y,
+                        0.35,
+                        0.01,
+                        &SpatialQueryFilter::default(),
+                    )
+                    .map(|(_, _, height)| height)
+                })
+                .unwrap()
+        };
+
+        let rounded = climb(&mut app, capsule).unwrap();
+        let flat = climb(&mut app, capsule.flat_bottomed()).unwrap();
+
+        // The rounded bottom rests on the edge of the ledge, lower than the flat one on top of it
+        assert!((flat - 0.2).abs() < 0.03, "climbed {flat}");
+        assert!(rounded < flat - 0.03, "climbed {rounded} instead of {flat}");
+    }
+
+    #[test]
+    fn mantling_below_a_flush_ceiling_fails() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+            // The bottom of the ceiling is 0.005 above the head of the capsule
+            spawn_slab(world, 1.915);
+        });
+
+        let mantle = app
+            .world_mut()
+            .run_system_once(|spatial_query: SpatialQuery| {
+                try_mantle(
+                    &spatial_query,
+                    &Collider::capsule(0.35, 1.0),
+                    Vec3::Y * 0.86,
+                    Quat::IDENTITY,
+                    Dir3::Y,
+                    Vec3::Z,
+                    1.0,
+                    0.2,
+                    0.01,
+                    &SpatialQueryFilter::default(),
+                    |_| PI / 4.0,
+                )
+            })
+            .unwrap();
+
+        assert_eq!(mantle, None);
+    }
+
+    /// Finds a spawn position for a capsule with a radius of `0.35` and a length of `1.0` near `near`.
+    fn spawn_position(app: &mut App, near: Vec3, search_radius: f32) -> Option<Vec3> {
+        app.world_mut()
+            .run_system_once(move |spatial_query: SpatialQuery| {
+                find_spawn_position(
+                    &spatial_query,
+                    &Collider::capsule(0.35, 1.0),
+                    MoveAndSlideConfig::default(),
+                    near,
+                    Dir3::Y,
+                    Quat::IDENTITY,
+                    &SpatialQueryFilter::default(),
+                    search_radius,
+                    |_| PI / 4.0,
+                )
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn spawn_position_rests_on_the_ground_below() {
+        let mut app = physics_app(|world| spawn_slab(world, 0.0));
+
+        let position = spawn_position(&mut app, Vec3::Y * 4.0, 5.0).unwrap();
+
+        assert!(position.xz().length() < 1e-4);
+        assert!((position.y - 0.86).abs() < 0.02, "spawned at {position}");
+    }
+
+    #[test]
+    fn spawn_position_avoids_obstacles() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+            // A pillar right at the requested position
+            world.spawn((
+                RigidBody::Static,
+                Collider::cuboid(1.0, 20.0, 1.0),
+                Transform::default(),
+            ));
+        });
+
+        let position = spawn_position(&mut app, Vec3::Y, 2.0).unwrap();
+
+        // Next to the pillar, on the ground
+        assert!(position.xz().length() > 0.85 && position.xz().length() <= 2.0 + 1e-4);
+        assert!((position.y - 0.86).abs() < 0.02, "spawned at {position}");
+    }
+
+    #[test]
+    fn spawn_position_inside_of_geometry_is_none() {
+        let mut app = physics_app(|world| {
+            world.spawn((
+                RigidBody::Static,
+                Collider::cuboid(20.0, 20.0, 20.0),
+                Transform::default(),
+            ));
+        });
+
+        assert_eq!(spawn_position(&mut app, Vec3::ZERO, 1.0), None);
+    }
+}
@@ -1,13 +1,21 @@
-use avian3d::prelude::*;
+use avian3d::{collision::collider::contact_query::contact, prelude::*};
 use bevy::prelude::*;
+use std::collections::VecDeque;
 
 const SIMILARITY_THRESHOLD: f32 = 0.999;
+/// How many times the epsilon further than the closest hit other hits can be to be considered simultaneous.
+const SIMULTANEOUS_HIT_WINDOW: f32 = 2.0;
+/// The maximum number of simultaneous hits collected by a single sweep.
+const MAX_SIMULTANEOUS_HITS: u32 = 4;
 
 /// Returns the safe hit distance and the hit data from the spatial query.
+///
+/// See [`MoveAndSlideConfig::ignore_origin_penetration`] for `ignore_origin_penetration`.
 #[must_use]
 pub fn sweep_check(
     collider: &Collider,
     epsilon: f32,
+    ignore_origin_penetration: bool,
     origin: Vec3,
     direction: Dir3,
     max_distance: f32,
@@ -23,7 +31,7 @@ pub fn sweep_check(
         &ShapeCastConfig {
             max_distance: max_distance + epsilon, // extend the trace slightly
             target_distance: epsilon, // I'm not sure what this does but I think this is correct ;)
-            ignore_origin_penetration: true,
+            ignore_origin_penetration,
             ..Default::default()
         },
         filter,
@@ -35,11 +43,100 @@ pub fn sweep_check(
     Some((safe_distance, hit))
 }
 
+/// Like [`sweep_check`], but returns every hit with a distinct plane at roughly the closest distance.
+///
+/// Hits up to `window` further than the closest hit are included, which lets an inside corner of two
+/// colliders be resolved at once instead of one plane at a time. The closest hit is always first.
+///
+/// Like [`sweep_check`] the safe distance is negative if the `collider` starts within `epsilon` of a surface.
+///
+/// Colliders are only hit once, so a corner within a single collider still only reports one plane.
+#[must_use]
+pub fn sweep_check_many(
+    collider: &Collider,
+    epsilon: f32,
+    ignore_origin_penetration: bool,
+    origin: Vec3,
+    direction: Dir3,
+    max_distance: f32,
+    rotation: Quat,
+    spatial_query: &SpatialQuery,
+    filter: &SpatialQueryFilter,
+    window: f32,
+) -> Option<(f32, Vec<ShapeHitData>)> {
+    let mut hits = spatial_query.shape_hits(
+        collider,
+        origin,
+        rotation,
+        direction,
+        MAX_SIMULTANEOUS_HITS,
+        &ShapeCastConfig {
+            max_distance: max_distance + epsilon, // extend the trace slightly
+            target_distance: epsilon,
+            ignore_origin_penetration,
+            ..Default::default()
+        },
+        filter,
+    );
+
+    hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+    let closest_distance = hits.first()?.distance;
+
+    // Only keep the simultaneous hits that add a new plane
+    let mut simultaneous_hits: Vec<ShapeHitData> = Vec::with_capacity(hits.len());
+    for hit in hits {
+        if hit.distance > closest_distance + window {
+            break;
+        }
+
+        if !simultaneous_hits
+            .iter()
+            .any(|other| similar_plane(other.normal1, hit.normal1))
+        {
+            simultaneous_hits.push(hit);
+        }
+    }
+
+    // How far is safe to translate by
+    let safe_distance = closest_distance - epsilon;
+
+    Some((safe_distance, simultaneous_hits))
+}
+
 /// Configuration for the move_and_slide function.
-#[derive(Clone, Copy)]
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
 pub struct MoveAndSlideConfig {
     pub max_substeps: u8,
     pub epsilon: f32,
+    /// Only hits with colliders that are members of these layers will trigger the `on_hit` callback.
+    ///
+    /// Collision response still happens for every hit.
+    pub callback_mask: LayerMask,
+    /// How many times [`depenetrate`] re-queries the overlaps, pushing out of one collider can push into another.
+    ///
+    /// `0` disables depenetration, [`move_and_slide`] then skips the overlap query entirely.
+    pub depenetration_iterations: u8,
+    /// The maximum total distance [`depenetrate`] can move the collider, to avoid launching it across the level.
+    pub max_depenetration: f32,
+    /// Widens the `epsilon` of the sweeps by this factor of the speed, making fast bodies less likely to tunnel through thin geometry.
+    ///
+    /// Fast bodies will stop slightly further away from surfaces, slow bodies keep the regular `epsilon`.
+    pub speed_skin_factor: f32,
+    /// Ignore colliders the body is already penetrating at the start of a sweep.
+    ///
+    /// When `false` sweeps report these as hits with a distance of zero, allowing custom depenetration.
+    /// Beware that bodies resting flush against a surface may then register as penetrating it.
+    pub ignore_origin_penetration: bool,
+    /// The maximum number of planes to slide along at once, the oldest planes are dropped first.
+    ///
+    /// Planes similar to an already retained plane replace it instead of taking up another slot.
+    pub max_planes: u8,
+    /// Stop instead of sliding when the slide direction turns away from the incoming velocity by more than this angle in radians.
+    ///
+    /// Lower this to stop jittering when wedged into narrow creases between steep walls.
+    pub min_slide_angle: f32,
 }
 
 impl Default for MoveAndSlideConfig {
@@ -47,6 +144,13 @@ impl Default for MoveAndSlideConfig {
         Self {
             max_substeps: 4,
             epsilon: 0.01,
+            callback_mask: LayerMask::ALL,
+            depenetration_iterations: 4,
+            max_depenetration: 1.0,
+            speed_skin_factor: 0.0,
+            ignore_origin_penetration: true,
+            max_planes: 4,
+            min_slide_angle: 179.0_f32.to_radians(),
         }
     }
 }
@@ -55,6 +159,27 @@ impl Default for MoveAndSlideConfig {
 pub struct MoveAndSlideResult {
     pub new_translation: Vec3,
     pub new_velocity: Vec3,
+    /// The number of distinct surfaces that were hit, similar planes are only counted once.
+    ///
+    /// `0` means nothing was hit, `1` a single wall or floor and `2` or more a corner or crevice.
+    pub contact_count: usize,
+    /// The number of sweeps that were performed, at most [`MoveAndSlideConfig::max_substeps`].
+    pub iterations_used: usize,
+    /// The normals of the planes that were slid along, in the order they were hit.
+    pub normals: Vec<Vec3>,
+    /// Why the movement stopped.
+    pub termination_reason: TerminationReason,
+}
+
+/// The reason the move_and_slide function stopped moving.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TerminationReason {
+    /// All of the motion was used up.
+    Clear,
+    /// The velocity was turned against the original velocity by sliding, e.g. in a corner.
+    VelocityReversed,
+    /// The [`MoveAndSlideConfig::max_substeps`] ran out before all of the motion was used up.
+    IterationsExhausted,
 }
 
 /// Hit data from the move_and_slide function.
@@ -77,6 +202,54 @@ pub struct MoveAndSlideHit<'a> {
     pub remaining_time: &'a mut f32,
 }
 
+impl MoveAndSlideHit<'_> {
+    /// Returns the time it takes to move `distance` at the current `velocity`.
+    ///
+    /// `move_and_slide` moves with the `velocity`, so time and distance are related by the speed:
+    /// `time = distance / velocity.length()`.
+    #[must_use]
+    pub fn time_for_distance(&self, distance: f32) -> f32 {
+        let speed = self.velocity.length();
+
+        if speed <= f32::EPSILON {
+            return 0.0;
+        }
+
+        distance / speed
+    }
+
+    /// Moves `distance` in the hit `direction` and subtracts the time this takes from the `remaining_time`.
+    ///
+    /// Use this when moving from within the `on_hit` callback (e.g. stepping up) to keep the
+    /// translation and the remaining time in sync.
+    pub fn consume_motion(&mut self, distance: f32) {
+        let time = self.time_for_distance(distance);
+
+        *self.translation += self.direction * distance;
+        *self.remaining_time = (*self.remaining_time - time).max(0.0);
+    }
+}
+
+/// Per-move context for [`move_and_slide`] that isn't part of the [`MoveAndSlideConfig`].
+pub struct MoveAndSlideContext<'c, M, S>
+where
+    M: Fn(Entity) -> LayerMask,
+    S: Fn(&ShapeHitData, Vec3) -> bool,
+{
+    /// Replaces the `collider` for the movement sweeps when set, e.g. a slightly shrunk capsule
+    /// to avoid snagging on edges. A sweep collider that is much smaller than the `collider` lets the `collider`
+    /// partially penetrate the geometry it slides along.
+    pub sweep_collider: Option<&'c Collider>,
+    /// Returns the collision layer memberships of the hit entity,
+    /// these are tested against the [`MoveAndSlideConfig::callback_mask`] before calling `on_hit`.
+    pub memberships: M,
+    /// Called with every hit and the current velocity, returning `false` ignores the hit entity
+    /// for the rest of the move, e.g. to pass up through one-way platforms. Ignored hits don't consume iterations,
+    /// aren't passed to `on_hit` and aren't part of the result. Overlaps are passed to it as well before moving,
+    /// see [`depenetrate`].
+    pub should_collide: S,
+}
+
 // @todo: lets make this take in a struct instead of a bunch of arguments,
 // that way each can be commented and we can also provide sane defaults, also ordering doesn't matter.
 
@@ -84,7 +257,11 @@ pub struct MoveAndSlideHit<'a> {
 /// velocity, and rotation.
 ///
 /// If `on_hit` returns `false` then the body will not slide during that iteration.
-pub fn move_and_slide(
+///
+/// The `collider` is pushed out of any geometry it starts inside of using [`depenetrate`] first, unless
+/// [`MoveAndSlideConfig::depenetration_iterations`] is `0`.
+/// `colliders` should return the collider, translation and rotation of the overlapping entity.
+pub fn move_and_slide<'a>(
     spatial_query: &SpatialQuery,
     collider: &Collider,
     mut translation: Vec3,
@@ -93,36 +270,106 @@ pub fn move_and_slide(
     config: MoveAndSlideConfig,
     filter: &SpatialQueryFilter,
     delta_time: f32,
+    context: MoveAndSlideContext<
+        impl Fn(Entity) -> LayerMask,
+        impl Fn(&ShapeHitData, Vec3) -> bool,
+    >,
+    colliders: impl Fn(Entity) -> Option<(&'a Collider, Vec3, Quat)>,
     mut on_hit: impl FnMut(&mut MoveAndSlideHit) -> bool,
 ) -> MoveAndSlideResult {
+    let MoveAndSlideContext {
+        sweep_collider,
+        memberships,
+        should_collide,
+    } = context;
+
+    // Get out of anything we were shoved into (e.g. a rising platform), the sweeps ignore it otherwise
+    if config.depenetration_iterations > 0 {
+        translation = depenetrate(
+            spatial_query,
+            collider,
+            translation,
+            rotation,
+            config,
+            filter,
+            |hit| should_collide(hit, velocity),
+            colliders,
+        );
+    }
+
     let Ok(original_direction) = Dir3::new(velocity) else {
         return MoveAndSlideResult {
             new_translation: translation,
             new_velocity: velocity,
+            contact_count: 0,
+            iterations_used: 0,
+            normals: Vec::new(),
+            termination_reason: TerminationReason::Clear,
         };
     };
 
     let mut remaining_time = delta_time;
+    let sweep_collider = sweep_collider.unwrap_or(collider);
 
-    let mut hits = Vec::with_capacity(config.max_substeps as usize);
+    // Use a wider skin at high speeds, the sweeps hit everything within the skin of the collider,
+    // so this sweeps a collider grown by the skin
+    let skin = config.epsilon + config.speed_skin_factor * velocity.length();
+
+    // Don't allocate up front, most moves don't hit anything
+    let mut hits = Vec::new();
+    // The most recent distinct planes to slide along, capped to keep solving cheap in deep pockets
+    let mut planes = VecDeque::new();
+    let mut iterations_used = 0;
+    let mut termination_reason = TerminationReason::IterationsExhausted;
+    // Only cloned once a hit is ignored, the ignored entities are excluded from the following sweeps
+    let mut ignore_filter: Option<SpatialQueryFilter> = None;
 
     for substep in 0..config.max_substeps {
         let Ok((direction, max_distance)) = Dir3::new_and_length(velocity * remaining_time) else {
+            termination_reason = TerminationReason::Clear;
             break;
         };
 
-        let Some((safe_movement, hit)) = sweep_check(
-            collider,
-            config.epsilon,
-            translation,
-            direction,
-            max_distance,
-            rotation,
-            spatial_query,
-            filter,
-        ) else {
-            // No collision, move the full remaining distance
+        iterations_used += 1;
+
+        // Collect every plane hit at once, so corners don't have to be resolved over multiple iterations
+        let sweep = loop {
+            let Some((safe_movement, new_hits)) = sweep_check_many(
+                sweep_collider,
+                skin,
+                config.ignore_origin_penetration,
+                translation,
+                direction,
+                max_distance,
+                rotation,
+                spatial_query,
+                ignore_filter.as_ref().unwrap_or(filter),
+                skin * SIMULTANEOUS_HIT_WINDOW,
+            ) else {
+                break None;
+            };
+
+            let ignored = new_hits
+                .iter()
+                .filter(|hit| !should_collide(hit, velocity))
+                .map(|hit| hit.entity)
+                .collect::<Vec<_>>();
+
+            if ignored.is_empty() {
+                break Some((safe_movement, new_hits));
+            }
+
+            // Sweep again without the ignored entities, they could hide hits behind them
+            ignore_filter
+                .get_or_insert_with(|| filter.clone())
+                .excluded_entities
+                .extend(ignored);
+        };
+
+        let Some((safe_movement, new_hits)) = sweep else {
+            // No collision, move the full remaining distance. The sweep covered all of it, so this can't tunnel
             translation += direction * max_distance;
+            termination_reason = TerminationReason::Clear;
             break;
         };
 
@@ -132,41 +379,212 @@ pub fn move_and_slide(
         // Move the transform to just before the point of collision
         translation += direction * safe_movement;
 
-        // Trigger callbacks
-        if !on_hit(&mut MoveAndSlideHit {
-            substep,
-            hit_data: hit,
-            translation: &mut translation,
-            velocity: &mut velocity,
-            direction,
-            motion: safe_movement,
-            remaining_motion: max_distance - safe_movement,
-            remaining_time: &mut remaining_time,
-        }) {
+        // Only trigger callbacks for hits within the callback mask, we still slide along everything else
+        let mut slide = true;
+        for hit in &new_hits {
+            let trigger_callback =
+                (memberships(hit.entity) & config.callback_mask) != LayerMask::NONE;
+
+            // Trigger callbacks
+            if trigger_callback
+                && !on_hit(&mut MoveAndSlideHit {
+                    substep,
+                    hit_data: *hit,
+                    translation: &mut translation,
+                    velocity: &mut velocity,
+                    direction,
+                    motion: safe_movement,
+                    remaining_motion: max_distance - safe_movement,
+                    remaining_time: &mut remaining_time,
+                })
+            {
+                slide = false;
+                break;
+            }
+        }
+
+        if !slide {
+            // The callback resolved the hit itself (e.g. by stepping up), report it as clear if that used up the motion
+            if (velocity * remaining_time).length() <= config.epsilon {
+                termination_reason = TerminationReason::Clear;
+                break;
+            }
+
             // User decided to not slide, continue to next substep
             continue;
         }
 
-        hits.push(hit.normal1);
+        for hit in &new_hits {
+            hits.push(hit.normal1);
 
-        velocity = solve_collision_planes(velocity, &hits, *original_direction);
+            // Replace similar planes so they're treated as the most recent hit
+            planes.retain(|plane| !similar_plane(*plane, hit.normal1));
+            planes.push_back(hit.normal1);
+
+            if planes.len() > config.max_planes.max(1) as usize {
+                planes.pop_front();
+            }
+        }
+
+        let slide_velocity =
+            solve_collision_planes(velocity, planes.make_contiguous(), *original_direction);
+
+        // Sliding (nearly) backwards means we're wedged in a crease, stop instead of jittering
+        velocity = match velocity.angle_between(slide_velocity) > config.min_slide_angle {
+            true => Vec3::ZERO,
+            false => slide_velocity,
+        };
 
         // Quake2: "If velocity is against original velocity, stop early to avoid tiny oscilations in sloping corners."
         if velocity.dot(*original_direction) <= 0.0 {
+            termination_reason = TerminationReason::VelocityReversed;
             break;
         }
     }
 
+    // When the iterations are exhausted the remaining motion is dropped instead of being applied unchecked
     MoveAndSlideResult {
         new_translation: translation,
         new_velocity: velocity,
+        contact_count: count_distinct_planes(&hits),
+        iterations_used,
+        normals: hits,
+        termination_reason,
+    }
+}
+
+/// Pushes the `collider` out of any geometry it's overlapping, returning the corrected translation.
+///
+/// `colliders` should return the collider, translation and rotation of the overlapping entity.
+/// Overlaps are resolved one at a time, so this is repeated up to [`MoveAndSlideConfig::depenetration_iterations`]
+/// times to resolve corners. The total correction is capped at [`MoveAndSlideConfig::max_depenetration`].
+///
+/// Overlaps for which `should_collide` returns `false` are left alone, e.g. a one-way platform the collider is
+/// passing through. The hit data has a `distance` of `0.0` and the surface of the other collider as `point1` and `normal1`.
+#[must_use]
+pub fn depenetrate<'a>(
+    spatial_query: &SpatialQuery,
+    collider: &Collider,
+    mut translation: Vec3,
+    rotation: Quat,
+    config: MoveAndSlideConfig,
+    filter: &SpatialQueryFilter,
+    should_collide: impl Fn(&ShapeHitData) -> bool,
+    colliders: impl Fn(Entity) -> Option<(&'a Collider, Vec3, Quat)>,
+) -> Vec3 {
+    let start_translation = translation;
+
+    for _ in 0..config.depenetration_iterations {
+        let mut resolved = true;
+
+        for entity in spatial_query.shape_intersections(collider, translation, rotation, filter) {
+            let Some((other, other_translation, other_rotation)) = colliders(entity) else {
+                continue;
+            };
+
+            let Ok(Some(contact)) = contact(
+                collider,
+                translation,
+                rotation,
+                other,
+                other_translation,
+                other_rotation,
+                0.0,
+            ) else {
+                continue;
+            };
+
+            if contact.penetration <= 0.0 {
+                continue;
+            }
+
+            let (position, other_position) = (Position(translation), Position(other_translation));
+            let (rotation, other_rotation) =
+                (Rotation::from(rotation), Rotation::from(other_rotation));
+
+            // The surface normal of the other collider
+            let normal = contact.global_normal2(&other_rotation);
+
+            let hit = ShapeHitData {
+                entity,
+                distance: 0.0,
+                point1: contact.global_point2(&other_position, &other_rotation),
+                point2: contact.global_point1(&position, &rotation),
+                normal1: normal,
+                normal2: contact.global_normal1(&rotation),
+            };
+
+            if !should_collide(&hit) {
+                continue;
+            }
+
+            // Push out along the surface normal of the other collider
+            translation += normal * (contact.penetration + config.epsilon);
+
+            resolved = false;
+        }
+
+        if resolved {
+            break;
+        }
+    }
+
+    start_translation + (translation - start_translation).clamp_length_max(config.max_depenetration)
+}
+
+/// Reflects the `velocity` off a surface with the given `normal`, useful for bouncing from within the `on_hit` callback.
+///
+/// The part of the velocity going into the surface is mirrored and scaled by `restitution`.
+/// A `restitution` of `1.0` preserves the speed, while `0.0` removes the velocity going into the surface.
+///
+/// The `normal` is expected to be normalized.
+#[must_use]
+pub fn reflect_velocity(velocity: Vec3, normal: Vec3, restitution: f32) -> Vec3 {
+    let into_surface = velocity.dot(normal);
+
+    // Already moving away from the surface
+    if into_surface >= 0.0 {
+        return velocity;
     }
+
+    velocity - normal * into_surface * (1.0 + restitution)
+}
+
+/// Clips the `velocity` against a plane with the given `normal`, the same way Quake does.
+///
+/// The part of the velocity going into the plane is removed and scaled by `overclip` (usually slightly above `1.0`)
+/// to push off the plane a little, while the part moving away from the plane is divided by `overclip` instead.
+///
+/// The `normal` is expected to be normalized.
+#[must_use]
+pub fn clip_velocity(velocity: Vec3, normal: Vec3, overclip: f32) -> Vec3 {
+    let mut backoff = velocity.dot(normal);
+
+    if backoff < 0.0 {
+        backoff *= overclip;
+    } else {
+        backoff /= overclip;
+    }
+
+    velocity - normal * backoff
 }
 
 fn similar_plane(normal1: Vec3, normal2: Vec3) -> bool {
     normal1.dot(normal2) > SIMILARITY_THRESHOLD
 }
 
+fn count_distinct_planes(normals: &[Vec3]) -> usize {
+    normals
+        .iter()
+        .enumerate()
+        .filter(|&(i, normal)| {
+            !normals[..i]
+                .iter()
+                .any(|previous| similar_plane(*previous, *normal))
+        })
+        .count()
+}
+
 fn solve_collision_planes(
     velocity: Vec3,
     hits: &[Vec3],
@@ -244,3 +662,714 @@ fn solve_collision_planes(
         })
         .unwrap_or_else(|vel| vel)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{ecs::system::RunSystemOnce, scene::ScenePlugin, time::TimeUpdateStrategy};
+    use core::{f32::consts::TAU, time::Duration};
+
+    /// A headless app with physics, stepped once so the spatial query knows about the spawned colliders.
+    fn physics_app(spawn: impl FnOnce(&mut World)) -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            // The collider constructors need the mesh and scene assets
+            AssetPlugin::default(),
+            ScenePlugin,
+            PhysicsPlugins::default(),
+        ));
+        app.init_asset::<Mesh>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / 64.0,
+        )));
+        spawn(app.world_mut());
+        app.finish();
+        app.cleanup();
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        app
+    }
+
+    fn spawn_slab(world: &mut World, height: f32) -> Entity {
+        world
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(4.0, 0.2, 4.0),
+                Transform::from_xyz(0.0, height, 0.0),
+            ))
+            .id()
+    }
+
+    /// Moves a unit sphere from `origin` with the `velocity` for a second, colliding only with the entities `should_collide` accepts.
+    fn move_sphere(
+        app: &mut App,
+        origin: Vec3,
+        velocity: Vec3,
+        config: MoveAndSlideConfig,
+        should_collide: impl Fn(Entity) -> bool + Send + Sync + 'static,
+        mut on_hit: impl FnMut(&mut MoveAndSlideHit) -> bool + Send + Sync + 'static,
+    ) -> MoveAndSlideResult {
+        app.world_mut()
+            .run_system_once(
+                move |spatial_query: SpatialQuery,
+                      colliders: Query<(&Collider, &Position, &Rotation)>| {
+                    move_and_slide(
+                        &spatial_query,
+                        &Collider::sphere(0.5),
+                        origin,
+                        velocity,
+                        Quat::IDENTITY,
+                        config,
+                        &SpatialQueryFilter::default(),
+                        1.0,
+                        MoveAndSlideContext {
+                            sweep_collider: None,
+                            memberships: |_| LayerMask::ALL,
+                            should_collide: |hit: &ShapeHitData, _| should_collide(hit.entity),
+                        },
+                        |entity| {
+                            colliders
+                                .get(entity)
+                                .ok()
+                                .map(|(collider, position, rotation)| {
+                                    (collider, position.0, rotation.0)
+                                })
+                        },
+                        &mut on_hit,
+                    )
+                },
+            )
+            .unwrap()
+    }
+
+    /// Pushes a unit sphere at `origin` out of the colliders with the `config`.
+    fn depenetrate_sphere(app: &mut App, origin: Vec3, config: MoveAndSlideConfig) -> Vec3 {
+        app.world_mut()
+            .run_system_once(
+                move |spatial_query: SpatialQuery,
+                      colliders: Query<(&Collider, &Position, &Rotation)>| {
+                    depenetrate(
+                        &spatial_query,
+                        &Collider::sphere(0.5),
+                        origin,
+                        Quat::IDENTITY,
+                        config,
+                        &SpatialQueryFilter::default(),
+                        |_| true,
+                        |entity| {
+                            colliders
+                                .get(entity)
+                                .ok()
+                                .map(|(collider, position, rotation)| {
+                                    (collider, position.0, rotation.0)
+                                })
+                        },
+                    )
+                },
+            )
+            .unwrap()
+    }
+
+    /// Spawns an inside corner with walls facing `-x` at `x` and facing `-z` at `z`.
+    fn spawn_corner(world: &mut World, x: f32, z: f32) -> (Entity, Entity) {
+        let x_wall = world
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(0.2, 4.0, 8.0),
+                Transform::from_xyz(x + 0.1, 0.0, 0.0),
+            ))
+            .id();
+        let z_wall = world
+            .spawn((
+                RigidBody::Static,
+                Collider::cuboid(8.0, 4.0, 0.2),
+                Transform::from_xyz(0.0, 0.0, z + 0.1),
+            ))
+            .id();
+
+        (x_wall, z_wall)
+    }
+
+    /// Sweeps a unit sphere from the origin diagonally into the corner, keeping hits up to `window` further than the closest.
+    fn sweep_into_corner(app: &mut App, window: f32) -> Vec<ShapeHitData> {
+        app.world_mut()
+            .run_system_once(move |spatial_query: SpatialQuery| {
+                sweep_check_many(
+                    &Collider::sphere(0.5),
+                    0.01,
+                    true,
+                    Vec3::ZERO,
+                    Dir3::new(Vec3::new(1.0, 0.0, 1.0)).unwrap(),
+                    4.0,
+                    Quat::IDENTITY,
+                    &spatial_query,
+                    &SpatialQueryFilter::default(),
+                    window,
+                )
+                .map(|(_, hits)| hits)
+                .unwrap_or_default()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn moving_up_stops_below_a_slab() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 2.0);
+        });
+
+        let result = move_sphere(
+            &mut app,
+            Vec3::ZERO,
+            Vec3::Y * 4.0,
+            MoveAndSlideConfig::default(),
+            |_| true,
+            |_| true,
+        );
+
+        assert!(result.new_translation.y < 1.5);
+        assert_eq!(result.contact_count, 1);
+    }
+
+    #[test]
+    fn moving_up_passes_through_a_rejected_slab() {
+        let mut slab = Entity::PLACEHOLDER;
+        let mut app = physics_app(|world| slab = spawn_slab(world, 2.0));
+
+        // Start partially inside of the slab, it must not push the sphere out either
+        let origin = Vec3::Y * 1.6;
+        let result = move_sphere(
+            &mut app,
+            origin,
+            Vec3::Y * 4.0,
+            MoveAndSlideConfig::default(),
+            move |entity| entity != slab,
+            |_| true,
+        );
+
+        assert!(
+            result
+                .new_translation
+                .abs_diff_eq(origin + Vec3::Y * 4.0, 1e-4)
+        );
+        assert_eq!(result.contact_count, 0);
+        assert_eq!(result.termination_reason, TerminationReason::Clear);
+    }
+
+    #[test]
+    fn reflect_velocity_bounces_off_surfaces() {
+        let velocity = Vec3::new(1.0, -2.0, 0.0);
+
+        assert_eq!(
+            reflect_velocity(velocity, Vec3::Y, 1.0),
+            Vec3::new(1.0, 2.0, 0.0)
+        );
+        assert_eq!(
+            reflect_velocity(velocity, Vec3::Y, 0.5),
+            Vec3::new(1.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            reflect_velocity(velocity, Vec3::Y, 0.0),
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn reflect_velocity_keeps_velocity_away_from_surfaces() {
+        let velocity = Vec3::new(1.0, 2.0, 0.0);
+
+        assert_eq!(reflect_velocity(velocity, Vec3::Y, 1.0), velocity);
+    }
+
+    #[test]
+    fn depenetration_pushes_out_of_overlaps() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+        });
+
+        // Sunk 0.2 into the top of the slab
+        let translation =
+            depenetrate_sphere(&mut app, Vec3::Y * 0.4, MoveAndSlideConfig::default());
+
+        assert!(translation.y >= 0.6 && translation.y < 0.6 + 0.05);
+    }
+
+    #[test]
+    fn depenetration_resolves_overlaps_with_multiple_colliders() {
+        let mut app = physics_app(|world| {
+            // The floor and two walls meeting in a corner at the origin
+            for (size, translation) in [
+                (Vec3::new(4.0, 1.0, 4.0), Vec3::new(0.0, -0.5, 0.0)),
+                (Vec3::new(1.0, 4.0, 4.0), Vec3::new(0.5, 0.0, 0.0)),
+                (Vec3::new(4.0, 4.0, 1.0), Vec3::new(0.0, 0.0, 0.5)),
+            ] {
+                world.spawn((
+                    RigidBody::Static,
+                    Collider::cuboid(size.x, size.y, size.z),
+                    Transform::from_translation(translation),
+                ));
+            }
+        });
+        let config = MoveAndSlideConfig {
+            max_depenetration: 2.0,
+            ..Default::default()
+        };
+
+        // Sunk into all three at once
+        let translation = depenetrate_sphere(&mut app, Vec3::new(0.05, -0.05, 0.05), config);
+
+        assert!(
+            translation.abs_diff_eq(Vec3::new(-0.51, 0.51, -0.51), 0.02),
+            "pushed out to {translation}"
+        );
+    }
+
+    #[test]
+    fn depenetration_is_clamped_to_the_max_distance() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+        });
+        let config = MoveAndSlideConfig {
+            max_depenetration: 0.1,
+            ..Default::default()
+        };
+
+        let translation = depenetrate_sphere(&mut app, Vec3::Y * 0.4, config);
+
+        assert!((translation.y - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn depenetration_without_iterations_does_nothing() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+        });
+        let config = MoveAndSlideConfig {
+            depenetration_iterations: 0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            depenetrate_sphere(&mut app, Vec3::Y * 0.4, config),
+            Vec3::Y * 0.4
+        );
+    }
+
+    #[test]
+    fn move_and_slide_without_depenetration_iterations_stays_in_overlaps() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+        });
+        let config = MoveAndSlideConfig {
+            depenetration_iterations: 0,
+            ..Default::default()
+        };
+
+        let result = move_sphere(
+            &mut app,
+            Vec3::Y * 0.4,
+            Vec3::ZERO,
+            config,
+            |_| true,
+            |_| true,
+        );
+
+        assert_eq!(result.new_translation, Vec3::Y * 0.4);
+    }
+
+    #[test]
+    fn similar_planes_are_counted_once() {
+        let tilted = Quat::from_rotation_z(0.01) * Vec3::Y;
+
+        assert_eq!(count_distinct_planes(&[]), 0);
+        assert_eq!(count_distinct_planes(&[Vec3::Y, tilted, Vec3::Y]), 1);
+        assert_eq!(count_distinct_planes(&[Vec3::Y, Vec3::X, tilted]), 2);
+        assert_eq!(count_distinct_planes(&[Vec3::X, Vec3::Y, Vec3::Z]), 3);
+    }
+
+    #[test]
+    fn clip_velocity_removes_motion_into_the_plane() {
+        let velocity = Vec3::new(1.0, -2.0, 0.0);
+
+        assert_eq!(clip_velocity(velocity, Vec3::Y, 1.0), Vec3::X);
+        // Overclipping pushes off the plane a little
+        assert!(clip_velocity(velocity, Vec3::Y, 1.01).y > 0.0);
+    }
+
+    #[test]
+    fn clip_velocity_divides_motion_away_from_the_plane() {
+        let velocity = Vec3::new(1.0, 2.0, 0.0);
+
+        assert_eq!(clip_velocity(velocity, Vec3::Y, 1.0), Vec3::X);
+        assert!(clip_velocity(velocity, Vec3::Y, 2.0).abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn sweep_check_many_returns_the_closest_hit_first() {
+        let mut walls = (Entity::PLACEHOLDER, Entity::PLACEHOLDER);
+        let mut app = physics_app(|world| walls = spawn_corner(world, 1.05, 1.0));
+
+        let hits = sweep_into_corner(&mut app, 0.2);
+
+        assert_eq!(
+            hits.iter().map(|hit| hit.entity).collect::<Vec<_>>(),
+            [walls.1, walls.0]
+        );
+        assert!(hits[0].distance <= hits[1].distance);
+    }
+
+    #[test]
+    fn sweep_check_many_skips_hits_outside_of_the_window() {
+        let mut walls = (Entity::PLACEHOLDER, Entity::PLACEHOLDER);
+        let mut app = physics_app(|world| walls = spawn_corner(world, 1.0, 1.5));
+
+        let hits = sweep_into_corner(&mut app, 0.02);
+
+        assert_eq!(
+            hits.iter().map(|hit| hit.entity).collect::<Vec<_>>(),
+            [walls.0]
+        );
+    }
+
+    #[test]
+    fn starting_within_the_skin_backs_out_of_it() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 0.0);
+        });
+
+        // Resting closer to the top of the slab than the default epsilon of 0.01
+        let result = move_sphere(
+            &mut app,
+            Vec3::Y * 0.604,
+            Vec3::NEG_Y,
+            MoveAndSlideConfig::default(),
+            |_| true,
+            |_| true,
+        );
+
+        let y = result.new_translation.y;
+        assert!(y > 0.604 && y < 0.614 + 1e-4, "moved to {y}");
+    }
+
+    #[test]
+    fn resolving_a_hit_in_the_callback_reports_the_move_as_clear() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 2.0);
+        });
+        let config = MoveAndSlideConfig {
+            max_substeps: 1,
+            ..Default::default()
+        };
+
+        // Pretend to step over the slab, using up the rest of the motion on the last substep
+        let result = move_sphere(
+            &mut app,
+            Vec3::ZERO,
+            Vec3::Y * 4.0,
+            config,
+            |_| true,
+            |hit| {
+                hit.consume_motion(hit.remaining_motion);
+                false
+            },
+        );
+
+        assert_eq!(result.termination_reason, TerminationReason::Clear);
+    }
+
+    #[test]
+    fn running_out_of_substeps_reports_the_iterations_exhausted() {
+        let mut app = physics_app(|world| {
+            spawn_slab(world, 2.0);
+        });
+        let config = MoveAndSlideConfig {
+            max_substeps: 1,
+            ..Default::default()
+        };
+
+        // Sliding along the slab needs another substep
+        let result = move_sphere(
+            &mut app,
+            Vec3::ZERO,
+            Vec3::new(1.0, 4.0, 0.0),
+            config,
+            |_| true,
+            |_| true,
+        );
+
+        assert_eq!(
+            result.termination_reason,
+            TerminationReason::IterationsExhausted
+        );
+    }
+
+    #[test]
+    fn callbacks_only_trigger_for_hits_within_the_callback_mask() {
+        let hazard = LayerMask(2);
+        let mut app = physics_app(|world| {
+            // A wall on the default layer next to the sphere and a hazard above it
+            world.spawn((
+                RigidBody::Static,
+                Collider::cuboid(0.2, 8.0, 8.0),
+                Transform::from_xyz(1.1, 0.0, 0.0),
+            ));
+            world.spawn((
+                RigidBody::Static,
+                Collider::cuboid(4.0, 0.2, 4.0),
+                Transform::from_xyz(0.0, 2.0, 0.0),
+                CollisionLayers::new(hazard, LayerMask::ALL),
+            ));
+        });
+        let config = MoveAndSlideConfig {
+            callback_mask: hazard,
+            ..Default::default()
+        };
+
+        let (result, hit_memberships) = app
+            .world_mut()
+            .run_system_once(
+                move |spatial_query: SpatialQuery, layers: Query<&CollisionLayers>| {
+                    let memberships = |entity: Entity| {
+                        layers.get(entity).copied().unwrap_or_default().memberships
+                    };
+                    let mut hit_memberships = Vec::new();
+
+                    let result = move_and_slide(
+                        &spatial_query,
+                        &Collider::sphere(0.5),
+                        Vec3::ZERO,
+                        Vec3::new(2.0, 4.0, 0.0),
+                        Quat::IDENTITY,
+                        config,
+                        &SpatialQueryFilter::default(),
+                        1.0,
+                        MoveAndSlideContext {
+                            sweep_collider: None,
+                            memberships,
+                            should_collide: |_: &ShapeHitData, _| true,
+                        },
+                        |_| None,
+                        |hit| {
+                            hit_memberships.push(memberships(hit.hit_data.entity));
+                            true
+                        },
+                    );
+
+                    (result, hit_memberships)
+                },
+            )
+            .unwrap();
+
+        // Only the hazard triggers the callback, the wall is still slid along
+        assert_eq!(hit_memberships, vec![hazard]);
+        assert!(result.new_translation.x < 0.5);
+        assert!(result.new_translation.y > 1.3);
+    }
+
+    #[test]
+    fn speed_skin_keeps_fast_bodies_further_from_thin_walls() {
+        let mut app = physics_app(|world| {
+            // A thin wall facing -x at x = 5
+            world.spawn((
+                RigidBody::Static,
+                Collider::cuboid(0.05, 8.0, 8.0),
+                Transform::from_xyz(5.025, 0.0, 0.0),
+            ));
+        });
+        let config = MoveAndSlideConfig {
+            speed_skin_factor: 0.005,
+            ..Default::default()
+        };
+
+        let fast = move_sphere(
+            &mut app,
+            Vec3::ZERO,
+            Vec3::X * 40.0,
+            config,
+            |_| true,
+            |_| true,
+        );
+        let regular = move_sphere(
+            &mut app,
+            Vec3::ZERO,
+            Vec3::X * 40.0,
+            MoveAndSlideConfig::default(),
+            |_| true,
+            |_| true,
+        );
+
+        // Stops the skin of 0.21 in front of the wall instead of the regular 0.01
+        assert!(fast.new_translation.x > 4.0 && fast.new_translation.x < 4.4);
+        assert!(regular.new_translation.x > 4.4 && regular.new_translation.x < 4.5);
+        assert_eq!(fast.contact_count, 1);
+    }
+
+    #[test]
+    fn speed_skin_lets_slow_bodies_through_narrow_gaps() {
+        let mut app = physics_app(|world| {
+            // A gap 0.02 wider than the sphere on either side
+            for z in [-1.02, 1.02] {
+                world.spawn((
+                    RigidBody::Static,
+                    Collider::cuboid(4.0, 8.0, 1.0),
+                    Transform::from_xyz(0.0, 0.0, z),
+                ));
+            }
+        });
+        let config = MoveAndSlideConfig {
+            speed_skin_factor: 0.005,
+            ..Default::default()
+        };
+
+        let origin = Vec3::X * -0.5;
+        let result = move_sphere(&mut app, origin, Vec3::X, config, |_| true, |_| true);
+
+        assert!(result.new_translation.abs_diff_eq(origin + Vec3::X, 1e-4));
+        assert_eq!(result.contact_count, 0);
+    }
+
+    /// Moves a unit sphere resting 0.005 from a wall along the wall, with `ignore_origin_penetration` set to `ignore`.
+    fn slide_along_flush_wall(ignore: bool) -> MoveAndSlideResult {
+        let mut app = physics_app(|world| {
+            // A wall facing -x at x = 1
+            world.spawn((
+                RigidBody::Static,
+                Collider::cuboid(0.2, 8.0, 8.0),
+                Transform::from_xyz(1.1, 0.0, 0.0),
+            ));
+        });
+        let config = MoveAndSlideConfig {
+            ignore_origin_penetration: ignore,
+            ..Default::default()
+        };
+
+        move_sphere(
+            &mut app,
+            Vec3::X * 0.495,
+            Vec3::Z,
+            config,
+            |_| true,
+            |_| true,
+        )
+    }
+
+    #[test]
+    fn ignoring_origin_penetration_slides_freely_along_a_flush_wall() {
+        let result = slide_along_flush_wall(true);
+
+        assert!(
+            result
+                .new_translation
+                .abs_diff_eq(Vec3::new(0.495, 0.0, 1.0), 1e-4)
+        );
+        assert_eq!(result.contact_count, 0);
+    }
+
+    #[test]
+    fn reporting_origin_penetration_hits_a_flush_wall() {
+        let result = slide_along_flush_wall(false);
+
+        // The wall within the epsilon registers as penetrating and stops the sphere
+        assert_eq!(result.contact_count, 1);
+        assert!(result.normals[0].abs_diff_eq(Vec3::NEG_X, 1e-4));
+        assert!(result.new_translation.z < 1.0);
+    }
+
+    #[test]
+    fn grinding_along_a_curved_wall_slides_smoothly() {
+        // The inside of a ring with a radius of 4, made out of 32 flat segments
+        let segments = 32;
+        let mut app = physics_app(|world| {
+            for i in 0..segments {
+                let angle = i as f32 * TAU / segments as f32;
+                world.spawn((
+                    RigidBody::Static,
+                    Collider::cuboid(0.2, 8.0, 0.85),
+                    Transform::from_translation(Vec3::new(angle.cos(), 0.0, angle.sin()) * 4.1)
+                        .with_rotation(Quat::from_rotation_y(-angle)),
+                ));
+            }
+        });
+        let config = MoveAndSlideConfig {
+            max_planes: 4,
+            ..Default::default()
+        };
+
+        let mut translation = Vec3::X * 3.48;
+        for step in 0..16 {
+            // Move along the wall while pushing into it
+            let outward = translation.normalize();
+            let tangent = Vec3::Y.cross(outward);
+            let result = move_sphere(
+                &mut app,
+                translation,
+                tangent + outward * 0.5,
+                config,
+                |_| true,
+                |_| true,
+            );
+
+            // Never snagged on the seams between the segments
+            let moved = result.new_translation.distance(translation);
+            assert!(moved > 0.8, "moved {moved} in step {step}");
+            assert!(
+                result.new_translation.length() < 3.5,
+                "went through the wall"
+            );
+            translation = result.new_translation;
+        }
+    }
+
+    #[test]
+    fn shrunk_sweep_collider_fits_through_a_tight_gap() {
+        let mut app = physics_app(|world| {
+            // A gap starting at x = 0 that is 0.04 narrower than the unit sphere
+            for z in [-0.98, 0.98] {
+                world.spawn((
+                    RigidBody::Static,
+                    Collider::cuboid(4.0, 8.0, 1.0),
+                    Transform::from_xyz(2.0, 0.0, z),
+                ));
+            }
+        });
+        let move_into_gap = |app: &mut App, sweep_radius: f32| {
+            app.world_mut()
+                .run_system_once(move |spatial_query: SpatialQuery| {
+                    move_and_slide(
+                        &spatial_query,
+                        &Collider::sphere(0.5),
+                        Vec3::NEG_X,
+                        Vec3::X * 3.0,
+                        Quat::IDENTITY,
+                        MoveAndSlideConfig::default(),
+                        &SpatialQueryFilter::default(),
+                        1.0,
+                        MoveAndSlideContext {
+                            sweep_collider: Some(&Collider::sphere(sweep_radius)),
+                            memberships: |_| LayerMask::ALL,
+                            should_collide: |_: &ShapeHitData, _| true,
+                        },
+                        |_| None,
+                        |_| true,
+                    )
+                })
+                .unwrap()
+        };
+
+        let full = move_into_gap(&mut app, 0.5);
+        let shrunk = move_into_gap(&mut app, 0.45);
+
+        assert!(full.new_translation.x < 0.0);
+        assert!(full.contact_count > 0);
+        assert!(
+            shrunk
+                .new_translation
+                .abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-4)
+        );
+        assert_eq!(shrunk.contact_count, 0);
+    }
+}